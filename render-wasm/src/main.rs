@@ -52,19 +52,42 @@ pub extern "C" fn set_render_options(debug: u32, dpr: f32) {
 #[no_mangle]
 pub unsafe extern "C" fn render() {
     let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
-    state.render_all(true);
+    if let Err(msg) = state.render_all(true) {
+        eprintln!("{}", msg);
+    }
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn render_without_cache() {
     let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
-    state.render_all(false);
+    if let Err(msg) = state.render_all(false) {
+        eprintln!("{}", msg);
+    }
+}
+
+/// Renders a frame and its content at the given scale, for exports and thumbnails.
+/// Returns a pointer to the size of the image (as a big-endian u32) followed by the
+/// image encoded as PNG, that must be released with [mem::free_bytes].
+#[no_mangle]
+pub extern "C" fn render_frame_image(a: u32, b: u32, c: u32, d: u32, scale: f32) -> *mut u8 {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    let id = uuid_from_u32_quartet(a, b, c, d);
+    let image = state
+        .render_frame_image(&id, scale)
+        .expect("could not render frame image");
+
+    let mut bytes = Vec::<u8>::with_capacity(size_of::<u32>() + image.len());
+    bytes.extend_from_slice(&(image.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&image);
+    mem::write_bytes(bytes)
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn navigate() {
     let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
-    state.navigate();
+    if let Err(msg) = state.navigate() {
+        eprintln!("{}", msg);
+    }
 }
 
 #[no_mangle]
@@ -118,7 +141,7 @@ pub unsafe extern "C" fn set_shape_kind_rect() {
     let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
 
     if let Some(shape) = state.current_shape() {
-        shape.set_kind(Kind::Rect(math::Rect::new_empty()));
+        shape.set_kind(Kind::Rect(math::Rect::new_empty(), None));
     }
 }
 
@@ -131,6 +154,42 @@ pub unsafe extern "C" fn set_shape_kind_path() {
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn set_shape_kind_bool(bool_type: i32) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape.set_kind(Kind::Bool(shapes::Bool::new(shapes::BoolType::from(
+            bool_type,
+        ))));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn set_shape_kind_text() {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape.set_kind(Kind::Text(
+            shapes::TextContent::new(math::Rect::new_empty()),
+        ));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn set_shape_kind_group() {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape.set_kind(Kind::Group);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn set_shape_kind_frame() {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape.set_kind(Kind::Frame(math::Rect::new_empty(), None));
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn set_shape_selrect(left: f32, top: f32, right: f32, bottom: f32) {
     let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
@@ -139,6 +198,14 @@ pub extern "C" fn set_shape_selrect(left: f32, top: f32, right: f32, bottom: f32
     }
 }
 
+#[no_mangle]
+pub extern "C" fn set_shape_corners(r1: f32, r2: f32, r3: f32, r4: f32) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape.set_corners((r1, r2, r3, r4));
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn set_shape_clip_content(clip_content: bool) {
     let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
@@ -269,6 +336,45 @@ pub extern "C" fn is_image_cached(a: u32, b: u32, c: u32, d: u32) -> bool {
     state.render_state().has_image(&id)
 }
 
+/// Stores the font in the buffer, which must be a TTF or OTF font. Returns whether it
+/// could be stored, so the font can be loaded again in another format when it could not
+/// (like WOFF2 fonts, which are not supported).
+#[no_mangle]
+pub extern "C" fn store_font(
+    a: u32,
+    b: u32,
+    c: u32,
+    d: u32,
+    weight: i32,
+    style: i32,
+    size: u32,
+) -> bool {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    let family = uuid_from_u32_quartet(a, b, c, d);
+    let key = render::FontKey::new(family, weight, shapes::FontStyle::from(style));
+
+    unsafe {
+        let font_bytes = Vec::<u8>::from_raw_parts(mem::buffer_ptr(), size as usize, size as usize);
+        let stored = match state.render_state().add_font(key, &font_bytes) {
+            Err(msg) => {
+                eprintln!("{}", msg);
+                false
+            }
+            _ => true,
+        };
+        mem::free_bytes();
+        stored
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn is_font_cached(a: u32, b: u32, c: u32, d: u32, weight: i32, style: i32) -> bool {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    let family = uuid_from_u32_quartet(a, b, c, d);
+    let key = render::FontKey::new(family, weight, shapes::FontStyle::from(style));
+    state.render_state().has_font(&key)
+}
+
 #[no_mangle]
 pub extern "C" fn add_shape_image_fill(
     a: u32,
@@ -298,6 +404,175 @@ pub extern "C" fn clear_shape_fills() {
     }
 }
 
+#[no_mangle]
+pub extern "C" fn add_shape_stroke(width: f32, raw_color: u32, style: i32, kind: i32) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        let color = skia::Color::new(raw_color);
+        shape.add_stroke(shapes::Stroke::new(
+            shapes::Fill::Solid(color),
+            width,
+            shapes::StrokeStyle::from(style),
+            shapes::StrokeKind::from(kind),
+        ));
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn set_shape_stroke_caps(cap_start: i32, cap_end: i32) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape
+            .set_stroke_caps(
+                shapes::StrokeCap::from(cap_start),
+                shapes::StrokeCap::from(cap_end),
+            )
+            .expect("could not set stroke caps");
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn set_shape_stroke_linear_fill(
+    start_x: f32,
+    start_y: f32,
+    end_x: f32,
+    end_y: f32,
+    opacity: f32,
+) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape
+            .set_stroke_fill(shapes::Fill::new_linear_gradient(
+                (start_x, start_y),
+                (end_x, end_y),
+                opacity,
+            ))
+            .expect("could not set stroke fill");
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn set_shape_stroke_radial_fill(
+    start_x: f32,
+    start_y: f32,
+    end_x: f32,
+    end_y: f32,
+    opacity: f32,
+    width: f32,
+) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape
+            .set_stroke_fill(shapes::Fill::new_radial_gradient(
+                (start_x, start_y),
+                (end_x, end_y),
+                opacity,
+                width,
+            ))
+            .expect("could not set stroke fill");
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn add_shape_stroke_stops(ptr: *mut shapes::RawStopData, n_stops: u32) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+
+    if let Some(shape) = state.current_shape() {
+        let len = n_stops as usize;
+
+        unsafe {
+            let buffer = Vec::<shapes::RawStopData>::from_raw_parts(ptr, len, len);
+            shape
+                .add_stroke_gradient_stops(buffer)
+                .expect("could not add gradient stops");
+            mem::free_bytes();
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn set_shape_stroke_image_fill(
+    a: u32,
+    b: u32,
+    c: u32,
+    d: u32,
+    alpha: f32,
+    width: i32,
+    height: i32,
+) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    let id = uuid_from_u32_quartet(a, b, c, d);
+    if let Some(shape) = state.current_shape() {
+        shape
+            .set_stroke_fill(shapes::Fill::new_image_fill(
+                id,
+                (alpha * 0xff as f32).floor() as u8,
+                (width, height),
+            ))
+            .expect("could not set stroke fill");
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn clear_shape_strokes() {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape.clear_strokes();
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn add_shape_shadow(
+    raw_color: u32,
+    blur: f32,
+    spread: f32,
+    x: f32,
+    y: f32,
+    style: i32,
+    hidden: bool,
+) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        let color = skia::Color::new(raw_color);
+        shape.add_shadow(shapes::Shadow::new(
+            color,
+            blur,
+            spread,
+            (x, y),
+            shapes::ShadowStyle::from(style),
+            hidden,
+        ));
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn clear_shape_shadows() {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape.clear_shadows();
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn set_shape_blur(blur_type: i32, value: f32, hidden: bool) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape.set_blur(shapes::Blur::new(
+            shapes::BlurType::from(blur_type),
+            value,
+            hidden,
+        ));
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn set_shape_mask(masked: bool, mask_type: i32) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape.set_mask(masked.then(|| shapes::MaskType::from(mask_type)));
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn set_shape_blend_mode(mode: i32) {
     let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
@@ -338,6 +613,207 @@ pub extern "C" fn set_shape_path_content() {
     }
 }
 
+#[no_mangle]
+pub extern "C" fn set_shape_path_fill_rule(fill_rule: i32) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape
+            .set_path_fill_rule(shapes::FillRule::from(fill_rule))
+            .expect("could not set fill rule");
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn set_shape_grow_type(grow_type: i32) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape
+            .set_text_grow_type(shapes::GrowType::from(grow_type))
+            .expect("could not set grow type");
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn set_shape_vertical_align(vertical_align: i32) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape
+            .set_text_vertical_align(shapes::VerticalAlign::from(vertical_align))
+            .expect("could not set vertical align");
+    }
+}
+
+/// Lays out the current text and returns a pointer to its width and height (as two
+/// big-endian f32 numbers, like the other outputs), that must be released with
+/// [mem::free_bytes]. Both are zero when the current shape is not a text.
+#[no_mangle]
+pub extern "C" fn get_text_dimensions() -> *mut u8 {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    let (width, height) = match state.current_text_dimensions() {
+        Ok(dimensions) => dimensions,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            (0., 0.)
+        }
+    };
+
+    let mut bytes = Vec::<u8>::with_capacity(2 * size_of::<f32>());
+    bytes.extend_from_slice(&width.to_be_bytes());
+    bytes.extend_from_slice(&height.to_be_bytes());
+    mem::write_bytes(bytes)
+}
+
+/// Turns the current text into a path with the outlines of its glyphs. Returns a
+/// pointer to the number of segments (as a big-endian u32) followed by the segments
+/// in the [shapes::RawPathData] format, that must be released with [mem::free_bytes].
+#[no_mangle]
+pub extern "C" fn convert_text_to_path() -> *mut u8 {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    let raw_segments = state
+        .convert_current_text_to_path()
+        .expect("could not convert text to path");
+
+    let mut bytes = Vec::<u8>::with_capacity(
+        size_of::<u32>() + raw_segments.len() * size_of::<shapes::RawPathData>(),
+    );
+    bytes.extend_from_slice(&(raw_segments.len() as u32).to_be_bytes());
+    for raw_segment in raw_segments.iter() {
+        bytes.extend_from_slice(&raw_segment.data);
+    }
+    mem::write_bytes(bytes)
+}
+
+#[no_mangle]
+pub extern "C" fn add_text_paragraph(text_align: i32, paragraph_spacing: f32) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape
+            .add_text_paragraph(shapes::Paragraph::new(
+                shapes::TextAlign::from(text_align),
+                paragraph_spacing,
+            ))
+            .expect("could not add text paragraph");
+    }
+}
+
+/// Adds a leaf to the last paragraph of the current text. The buffer allocated with
+/// [mem::alloc_bytes] must hold its styles and text, as described in [shapes::RawTextLeafData].
+#[no_mangle]
+pub extern "C" fn add_text_leaf() {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        let leaf = shapes::TextLeaf::try_from(mem::bytes()).expect("could not read text leaf");
+        shape.add_text_leaf(leaf).expect("could not add text leaf");
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn add_text_leaf_solid_fill(raw_color: u32) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        let color = skia::Color::new(raw_color);
+        shape
+            .add_text_leaf_fill(shapes::Fill::Solid(color))
+            .expect("could not add text leaf fill");
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn add_text_leaf_linear_fill(
+    start_x: f32,
+    start_y: f32,
+    end_x: f32,
+    end_y: f32,
+    opacity: f32,
+) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape
+            .add_text_leaf_fill(shapes::Fill::new_linear_gradient(
+                (start_x, start_y),
+                (end_x, end_y),
+                opacity,
+            ))
+            .expect("could not add text leaf fill");
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn add_text_leaf_radial_fill(
+    start_x: f32,
+    start_y: f32,
+    end_x: f32,
+    end_y: f32,
+    opacity: f32,
+    width: f32,
+) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    if let Some(shape) = state.current_shape() {
+        shape
+            .add_text_leaf_fill(shapes::Fill::new_radial_gradient(
+                (start_x, start_y),
+                (end_x, end_y),
+                opacity,
+                width,
+            ))
+            .expect("could not add text leaf fill");
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn add_text_leaf_fill_stops(ptr: *mut shapes::RawStopData, n_stops: u32) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+
+    if let Some(shape) = state.current_shape() {
+        let len = n_stops as usize;
+
+        unsafe {
+            let buffer = Vec::<shapes::RawStopData>::from_raw_parts(ptr, len, len);
+            shape
+                .add_text_leaf_gradient_stops(buffer)
+                .expect("could not add gradient stops");
+            mem::free_bytes();
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn add_text_leaf_image_fill(
+    a: u32,
+    b: u32,
+    c: u32,
+    d: u32,
+    alpha: f32,
+    width: i32,
+    height: i32,
+) {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    let id = uuid_from_u32_quartet(a, b, c, d);
+    if let Some(shape) = state.current_shape() {
+        shape
+            .add_text_leaf_fill(shapes::Fill::new_image_fill(
+                id,
+                (alpha * 0xff as f32).floor() as u8,
+                (width, height),
+            ))
+            .expect("could not add text leaf fill");
+    }
+}
+
+/// Turns the current shape into an imported SVG. Its UTF-8 encoded source must be in
+/// the buffer allocated with [mem::alloc_bytes].
+#[no_mangle]
+pub extern "C" fn set_shape_svg_raw_content() {
+    let state = unsafe { STATE.as_mut() }.expect("got an invalid state pointer");
+    let font_mgr = state.render_state.fonts().font_mgr();
+    if let Some(shape) = state.current_shape() {
+        let content = String::from_utf8(mem::bytes()).expect("invalid UTF-8 SVG content");
+        shape
+            .set_svg_raw_content(content, font_mgr)
+            .expect("could not set SVG content");
+    }
+}
+
 fn main() {
     init_gl();
 }
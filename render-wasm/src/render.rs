@@ -5,6 +5,7 @@ use skia_safe as skia;
 use uuid::Uuid;
 
 use crate::math;
+use crate::shapes::MaskType;
 use crate::view::Viewbox;
 
 mod blend;
@@ -19,11 +20,23 @@ pub use blend::BlendMode;
 pub use images::*;
 
 pub trait Renderable {
-    fn render(&self, surface: &mut skia::Surface, images: &ImageStore) -> Result<(), String>;
+    fn render(
+        &self,
+        surface: &mut skia::Surface,
+        images: &ImageStore,
+        fonts: &FontStore,
+    ) -> Result<(), String>;
     fn blend_mode(&self) -> BlendMode;
     fn opacity(&self) -> f32;
     fn bounds(&self) -> math::Rect;
+    fn clip_bounds(&self) -> skia::RRect;
+    fn extrect(&self) -> math::Rect;
+    fn outline(&self) -> skia::Path;
+    fn layer_blur(&self) -> Option<f32>;
+    fn background_blur(&self) -> Option<f32>;
     fn hidden(&self) -> bool;
+    fn mask(&self) -> Option<MaskType>;
+    fn is_composited(&self) -> bool;
     fn clip(&self) -> bool;
     fn children_ids(&self) -> Vec<Uuid>;
 }
@@ -49,6 +62,7 @@ pub(crate) struct RenderState {
     options: RenderOptions,
     pub viewbox: Viewbox,
     images: ImageStore,
+    fonts: FontStore,
 }
 
 impl RenderState {
@@ -72,17 +86,30 @@ impl RenderState {
             options: RenderOptions::default(),
             viewbox: Viewbox::new(width as f32, height as f32),
             images: ImageStore::new(),
+            fonts: FontStore::new(),
         }
     }
 
     pub fn add_image(&mut self, id: Uuid, image_data: &[u8]) -> Result<(), String> {
-        self.images.add(id, image_data)
+        self.images.add(id, image_data, self.fonts.font_mgr())
     }
 
     pub fn has_image(&mut self, id: &Uuid) -> bool {
         self.images.contains(id)
     }
 
+    pub fn add_font(&mut self, key: FontKey, font_data: &[u8]) -> Result<(), String> {
+        self.fonts.add(key, font_data)
+    }
+
+    pub fn has_font(&self, key: &FontKey) -> bool {
+        self.fonts.contains(key)
+    }
+
+    pub fn fonts(&self) -> &FontStore {
+        &self.fonts
+    }
+
     pub fn set_debug_flags(&mut self, debug: u32) {
         self.options.debug_flags = debug;
     }
@@ -144,14 +171,18 @@ impl RenderState {
             .reset_matrix();
     }
 
-    pub fn render_single_element(&mut self, element: &impl Renderable) {
+    /// Draws the element onto the final surface. Composited elements are drawn into
+    /// their own layer, which already applies their opacity and blend mode.
+    pub fn render_single_element(&mut self, element: &impl Renderable, composited: bool) {
         element
-            .render(&mut self.drawing_surface, &self.images)
+            .render(&mut self.drawing_surface, &self.images, &self.fonts)
             .unwrap();
 
         let mut paint = skia::Paint::default();
-        paint.set_blend_mode(element.blend_mode().into());
-        paint.set_alpha_f(element.opacity());
+        if !composited {
+            paint.set_blend_mode(element.blend_mode().into());
+            paint.set_alpha_f(element.opacity());
+        }
 
         self.drawing_surface.draw(
             &mut self.final_surface.canvas(),
@@ -204,6 +235,59 @@ impl RenderState {
         self.flush();
     }
 
+    /// Renders a shape and its children alone at the given scale, and returns the
+    /// image encoded as PNG. It is used to export frames and render their thumbnails.
+    pub fn render_shape_image(
+        &mut self,
+        id: &Uuid,
+        tree: &HashMap<Uuid, impl Renderable>,
+        scale: f32,
+    ) -> Result<Vec<u8>, String> {
+        let bounds = tree.get(id).ok_or("Shape not found")?.bounds();
+        let width = (bounds.width() * scale).ceil().max(1.) as i32;
+        let height = (bounds.height() * scale).ceil().max(1.) as i32;
+
+        let final_surface = self
+            .final_surface
+            .new_surface_with_dimensions((width, height))
+            .ok_or("Could not create the image surface")?;
+        let drawing_surface = self
+            .final_surface
+            .new_surface_with_dimensions((width, height))
+            .ok_or("Could not create the image surface")?;
+        let final_surface = std::mem::replace(&mut self.final_surface, final_surface);
+        let drawing_surface = std::mem::replace(&mut self.drawing_surface, drawing_surface);
+
+        // The viewbox is moved over the shape, with a zoom that makes up for the device
+        // pixel ratio so the shape is drawn at the given scale.
+        let viewbox = self.viewbox;
+        let dpr = self.options.dpr();
+        self.viewbox = Viewbox::new(width as f32 / dpr, height as f32 / dpr);
+        self.viewbox.set_all(scale / dpr, -bounds.left, -bounds.top);
+
+        self.final_surface.canvas().clear(skia::Color::TRANSPARENT);
+        self.drawing_surface
+            .canvas()
+            .clear(skia::Color::TRANSPARENT);
+        self.scale(scale, scale);
+        self.translate(self.viewbox.pan_x, self.viewbox.pan_y);
+
+        self.render_shape_tree(id, tree);
+        let image = self.final_surface.image_snapshot();
+
+        self.final_surface = final_surface;
+        self.drawing_surface = drawing_surface;
+        self.viewbox = viewbox;
+
+        let data = skia::png_encoder::encode_image(
+            &mut self.gpu_state.context,
+            &image,
+            &skia::png_encoder::Options::default(),
+        )
+        .ok_or("Could not encode the shape image")?;
+        Ok(data.as_bytes().to_vec())
+    }
+
     fn render_all_from_cache(&mut self) -> Result<(), String> {
         self.reset_canvas();
 
@@ -266,7 +350,7 @@ impl RenderState {
         });
         paint.set_stroke_width(1.);
 
-        let mut scaled_rect = element.bounds();
+        let mut scaled_rect = element.extrect();
         let x = 100. + scaled_rect.x() * 0.2;
         let y = 100. + scaled_rect.y() * 0.2;
         let width = scaled_rect.width() * 0.2;
@@ -287,13 +371,78 @@ impl RenderState {
         );
     }
 
+    fn save_layer_blur(&mut self, sigma: f32) {
+        // The final surface is not scaled, so the blur must be scaled to device pixels
+        let scale = self.viewbox.zoom * self.options.dpr();
+        let mut paint = skia::Paint::default();
+        paint.set_image_filter(skia::image_filters::blur(
+            (sigma * scale, sigma * scale),
+            None,
+            None,
+            None,
+        ));
+
+        let layer_rec = skia::canvas::SaveLayerRec::default().paint(&paint);
+        self.final_surface.canvas().save_layer(&layer_rec);
+    }
+
+    // Blurs what is already composited on the final surface beneath the element outline
+    fn render_background_blur(&mut self, element: &impl Renderable, sigma: f32) {
+        let matrix = self.drawing_surface.canvas().local_to_device_as_3x3();
+        let filter = skia::image_filters::blur((sigma, sigma), skia::TileMode::Clamp, None, None);
+
+        let canvas = self.final_surface.canvas();
+        canvas.save();
+        canvas.concat(&matrix);
+        canvas.clip_path(&element.outline(), skia::ClipOp::Intersect, true);
+        if let Some(filter) = filter {
+            let layer_rec = skia::canvas::SaveLayerRec::default().backdrop(&filter);
+            canvas.save_layer(&layer_rec);
+            canvas.restore();
+        }
+        canvas.restore();
+    }
+
+    // The children are composited into a layer, and then the mask is drawn over them in
+    // another layer that only keeps the parts of the children covered by the mask.
+    fn render_masked_children(
+        &mut self,
+        mask_type: MaskType,
+        mask_id: &Uuid,
+        children_ids: &[Uuid],
+        tree: &HashMap<Uuid, impl Renderable>,
+    ) -> bool {
+        let mut is_complete = true;
+
+        self.final_surface
+            .canvas()
+            .save_layer(&skia::canvas::SaveLayerRec::default());
+        for id in children_ids.iter() {
+            is_complete = self.render_shape_tree(id, tree) && is_complete;
+        }
+
+        let mut mask_paint = skia::Paint::default();
+        mask_paint.set_blend_mode(skia::BlendMode::DstIn);
+        if mask_type == MaskType::Luminance {
+            mask_paint.set_color_filter(skia::luma_color_filter::new());
+        }
+        let mask_rec = skia::canvas::SaveLayerRec::default().paint(&mask_paint);
+        self.final_surface.canvas().save_layer(&mask_rec);
+        is_complete = self.render_shape_tree(mask_id, tree) && is_complete;
+        self.final_surface.canvas().restore();
+
+        self.final_surface.canvas().restore();
+
+        is_complete
+    }
+
     // Returns a boolean indicating if the viewbox contains the rendered shapes
     fn render_shape_tree(&mut self, root_id: &Uuid, tree: &HashMap<Uuid, impl Renderable>) -> bool {
         let element = tree.get(&root_id).unwrap();
-        let mut is_complete = self.viewbox.area.contains(element.bounds());
+        let mut is_complete = self.viewbox.area.contains(element.extrect());
 
         if !root_id.is_nil() {
-            if !element.bounds().intersects(self.viewbox.area) || element.hidden() {
+            if !element.extrect().intersects(self.viewbox.area) || element.hidden() {
                 self.render_debug_element(element, false);
                 // TODO: This means that not all the shapes are renderer so we
                 // need to call a render_all on the zoom out.
@@ -304,14 +453,44 @@ impl RenderState {
         }
 
         // This is needed so the next non-children shape does not carry this shape's transform
-        self.final_surface.canvas().save();
+        match element.layer_blur() {
+            // The shape and its children are composited into a layer that gets blurred on restore
+            Some(sigma) if !root_id.is_nil() => {
+                self.save_layer_blur(sigma);
+            }
+            _ => {
+                self.final_surface.canvas().save();
+            }
+        }
         self.drawing_surface.canvas().save();
 
+        // The backdrop is blurred before the layer below is opened, as it starts empty
+        if !root_id.is_nil() {
+            if let Some(sigma) = element.background_blur() {
+                self.render_background_blur(element, sigma);
+            }
+        }
+
+        // Groups and frames are composited as a whole, so their opacity and blend mode
+        // apply to them and their children together instead of to each of them. The
+        // layer is only opened when needed, as the background blur of the children
+        // reads the backdrop from it.
+        let is_composited = !root_id.is_nil()
+            && element.is_composited()
+            && (element.opacity() < 1. || element.blend_mode() != BlendMode::default());
+        if is_composited {
+            let mut paint = skia::Paint::default();
+            paint.set_blend_mode(element.blend_mode().into());
+            paint.set_alpha_f(element.opacity());
+            let layer_rec = skia::canvas::SaveLayerRec::default().paint(&paint);
+            self.final_surface.canvas().save_layer(&layer_rec);
+        }
+
         if !root_id.is_nil() {
-            self.render_single_element(element);
+            self.render_single_element(element, is_composited);
             if element.clip() {
-                self.drawing_surface.canvas().clip_rect(
-                    element.bounds(),
+                self.drawing_surface.canvas().clip_rrect(
+                    element.clip_bounds(),
                     skia::ClipOp::Intersect,
                     true,
                 );
@@ -319,10 +498,22 @@ impl RenderState {
         }
 
         // draw all the children shapes
-        for id in element.children_ids() {
-            is_complete = self.render_shape_tree(&id, tree) && is_complete;
+        let children_ids = element.children_ids();
+        match (element.mask(), children_ids.split_first()) {
+            (Some(mask_type), Some((mask_id, children_ids))) => {
+                is_complete = self.render_masked_children(mask_type, mask_id, children_ids, tree)
+                    && is_complete;
+            }
+            _ => {
+                for id in children_ids.iter() {
+                    is_complete = self.render_shape_tree(id, tree) && is_complete;
+                }
+            }
         }
 
+        if is_composited {
+            self.final_surface.canvas().restore();
+        }
         self.final_surface.canvas().restore();
         self.drawing_surface.canvas().restore();
 
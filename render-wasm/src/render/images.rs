@@ -1,21 +1,101 @@
-use skia_safe as skia;
-use std::collections::HashMap;
+use skia_safe::{self as skia, textlayout};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+use crate::shapes::FontStyle;
+
 pub type Image = skia::Image;
 
+/// The family used when a text asks for a font that has not been stored.
+pub const DEFAULT_FONT: &str = "sourcesanspro";
+
+static DEFAULT_FONT_BYTES: &[u8] = include_bytes!("../fonts/sourcesanspro-regular.ttf");
+
+/// Largest side, in pixels, an SVG image is rasterized to.
+const MAX_VECTOR_IMAGE_SIZE: i32 = 4096;
+
+/// Size used for SVG images that declare neither their size nor their view box.
+const DEFAULT_VECTOR_IMAGE_SIZE: f32 = 100.;
+
+/// Number of SVG image rasters kept before the cache is dropped.
+const MAX_CACHED_RASTERS: usize = 256;
+
+/// SVG images have no intrinsic resolution, so they are kept as pictures. Their texts
+/// are laid out with the fonts of the given manager.
+fn picture_from_svg(data: &[u8], font_mgr: skia::FontMgr) -> Result<skia::Picture, String> {
+    let mut dom = skia::svg::Dom::from_bytes(data, font_mgr).map_err(|err| err.to_string())?;
+
+    // SVGs without width and height take the size of their view box, so they keep
+    // its aspect ratio
+    let mut size = dom.root().intrinsic_size();
+    if size.is_empty() {
+        size = dom
+            .root()
+            .view_box()
+            .map(|view_box| view_box.size())
+            .filter(|size| !size.is_empty())
+            .unwrap_or(skia::Size::new(
+                DEFAULT_VECTOR_IMAGE_SIZE,
+                DEFAULT_VECTOR_IMAGE_SIZE,
+            ));
+        dom.set_container_size(size);
+    }
+
+    let mut recorder = skia::PictureRecorder::new();
+    let canvas = recorder.begin_recording(skia::Rect::from_size(size), None);
+    dom.render(canvas);
+    recorder
+        .finish_recording_as_picture(None)
+        .ok_or("Error recording SVG image".to_string())
+}
+
+fn rasterize_picture(picture: &skia::Picture, size: skia::ISize) -> Option<Image> {
+    let bounds = picture.cull_rect();
+    let mut surface = skia::surfaces::raster_n32_premul(size)?;
+
+    let canvas = surface.canvas();
+    canvas.scale((
+        size.width as f32 / bounds.width(),
+        size.height as f32 / bounds.height(),
+    ));
+    canvas.translate((-bounds.left, -bounds.top));
+    canvas.draw_picture(picture, None, None);
+
+    Some(surface.image_snapshot())
+}
+
 pub struct ImageStore {
     images: HashMap<Uuid, Image>,
+    vector_images: HashMap<Uuid, skia::Picture>,
+    // SVG images are rasterized when they are drawn, at the size they are drawn at
+    rasters: RefCell<HashMap<(Uuid, (i32, i32)), Image>>,
 }
 
 impl ImageStore {
     pub fn new() -> Self {
         Self {
             images: HashMap::with_capacity(2048),
+            vector_images: HashMap::new(),
+            rasters: RefCell::new(HashMap::new()),
         }
     }
 
-    pub fn add(&mut self, id: Uuid, image_data: &[u8]) -> Result<(), String> {
+    pub fn add(
+        &mut self,
+        id: Uuid,
+        image_data: &[u8],
+        font_mgr: skia::FontMgr,
+    ) -> Result<(), String> {
+        if is_svg(image_data) {
+            let picture = picture_from_svg(image_data, font_mgr)?;
+            self.vector_images.insert(id, picture);
+            self.rasters
+                .borrow_mut()
+                .retain(|(raster_id, _), _| *raster_id != id);
+            return Ok(());
+        }
+
         let image_data = skia::Data::new_copy(image_data);
         let image = Image::from_encoded(image_data).ok_or("Error decoding image data")?;
 
@@ -24,10 +104,126 @@ impl ImageStore {
     }
 
     pub fn contains(&mut self, id: &Uuid) -> bool {
-        self.images.contains_key(id)
+        self.images.contains_key(id) || self.vector_images.contains_key(id)
+    }
+
+    /// The image to draw at the given size, in device pixels. SVG images are
+    /// rasterized at that size, so they stay sharp at any zoom.
+    pub fn get(&self, id: &Uuid, size: skia::ISize) -> Option<Image> {
+        if let Some(image) = self.images.get(id) {
+            return Some(image.clone());
+        }
+
+        let picture = self.vector_images.get(id)?;
+        let scale = (MAX_VECTOR_IMAGE_SIZE as f32 / size.width.max(size.height) as f32).min(1.);
+        let size = (
+            ((size.width as f32 * scale) as i32).max(1),
+            ((size.height as f32 * scale) as i32).max(1),
+        );
+
+        let mut rasters = self.rasters.borrow_mut();
+        if let Some(image) = rasters.get(&(*id, size)) {
+            return Some(image.clone());
+        }
+
+        if rasters.len() >= MAX_CACHED_RASTERS {
+            rasters.clear();
+        }
+        let image = rasterize_picture(picture, size.into())?;
+        rasters.insert((*id, size), image.clone());
+        Some(image)
+    }
+}
+
+/// Raster formats are binary, so any payload starting like an XML document is
+/// taken as an SVG (its prolog, comments and doctype can be of any length).
+fn is_svg(data: &[u8]) -> bool {
+    let data = data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(data);
+    data.iter()
+        .find(|byte| !byte.is_ascii_whitespace())
+        .is_some_and(|byte| *byte == b'<')
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FontKey {
+    family: Uuid,
+    weight: i32,
+    style: FontStyle,
+}
+
+impl FontKey {
+    pub fn new(family: Uuid, weight: i32, style: FontStyle) -> Self {
+        Self {
+            family,
+            weight,
+            style,
+        }
+    }
+}
+
+pub struct FontStore {
+    font_mgr: skia::FontMgr,
+    font_provider: textlayout::TypefaceFontProvider,
+    font_collection: textlayout::FontCollection,
+    fonts: HashSet<FontKey>,
+}
+
+impl FontStore {
+    pub fn new() -> Self {
+        let font_mgr = skia::FontMgr::default();
+        let mut font_provider = textlayout::TypefaceFontProvider::new();
+
+        let default_font = font_mgr
+            .new_from_data(DEFAULT_FONT_BYTES, None)
+            .expect("Error decoding the default font");
+        font_provider.register_typeface(default_font, DEFAULT_FONT);
+
+        // The collection shares the provider, so fonts added later are found as well
+        let mut font_collection = textlayout::FontCollection::new();
+        font_collection
+            .set_default_font_manager(skia::FontMgr::from(font_provider.clone()), DEFAULT_FONT);
+
+        Self {
+            font_mgr,
+            font_provider,
+            font_collection,
+            fonts: HashSet::new(),
+        }
+    }
+
+    /// Registers a TTF or OTF font under the family of its key. Skia picks the
+    /// typeface matching the weight and style of each text among the ones in a family.
+    pub fn add(&mut self, key: FontKey, font_data: &[u8]) -> Result<(), String> {
+        // skia is built without the WOFF2 decoder
+        if font_data.starts_with(b"wOF2") {
+            return Err("WOFF2 fonts are not supported".to_string());
+        }
+
+        let typeface = self
+            .font_mgr
+            .new_from_data(font_data, None)
+            .ok_or("Error decoding font data")?;
+
+        let alias = key.family.to_string();
+        self.font_provider
+            .register_typeface(typeface, alias.as_str());
+        // texts laid out before the font was loaded have cached their fallback font
+        self.font_collection.clear_caches();
+
+        self.fonts.insert(key);
+        Ok(())
+    }
+
+    pub fn contains(&self, key: &FontKey) -> bool {
+        self.fonts.contains(key)
+    }
+
+    /// A font manager with the stored fonts, for the texts of imported SVGs.
+    pub fn font_mgr(&self) -> skia::FontMgr {
+        skia::FontMgr::from(self.font_provider.clone())
     }
 
-    pub fn get(&self, id: &Uuid) -> Option<&Image> {
-        self.images.get(id)
+    pub fn font_collection(&self) -> &textlayout::FontCollection {
+        &self.font_collection
     }
 }
@@ -2,28 +2,157 @@ use crate::math;
 use skia_safe as skia;
 use uuid::Uuid;
 
-use crate::render::BlendMode;
+use crate::render::{BlendMode, FontStore};
 
+mod blurs;
+mod bools;
 mod fills;
 mod images;
+mod masks;
 mod matrix;
 mod paths;
 mod renderable;
+mod shadows;
+mod strokes;
+mod svgraw;
+mod text;
 
+pub use blurs::*;
+pub use bools::*;
 pub use fills::*;
 pub use images::*;
+pub use masks::*;
 use matrix::*;
 pub use paths::*;
+pub use shadows::*;
+pub use strokes::*;
+pub use svgraw::*;
+pub use text::*;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Kind {
-    Rect(math::Rect),
+    Rect(math::Rect, Option<Corners>),
     Circle(math::Rect),
     Path(Path),
+    Text(TextContent),
+    Bool(Bool),
+    SVGRaw(SVGRaw),
+    Group,
+    Frame(math::Rect, Option<Corners>),
+}
+
+impl Kind {
+    /// The outline of the shape. Its starting point and direction match the ones
+    /// used by the SVG renderer, so dashed strokes start at the same place.
+    pub fn to_skia_path(&self) -> skia::Path {
+        match self {
+            // starts at the top left corner, clockwise
+            Kind::Rect(rect, None) | Kind::Frame(rect, None) => {
+                skia::Path::rect(rect, skia::PathDirection::CW)
+            }
+            // starts at the end of the top left corner, clockwise
+            Kind::Rect(rect, Some(corners)) | Kind::Frame(rect, Some(corners)) => {
+                let rrect = skia::RRect::new_rect_radii(rect, corners);
+                skia::Path::rrect_with_start_index(rrect, skia::PathDirection::CW, 0)
+            }
+            // starts at the rightmost point, clockwise
+            Kind::Circle(rect) => {
+                skia::Path::oval_with_start_index(rect, skia::PathDirection::CW, 1)
+            }
+            Kind::Path(path) => path.to_skia_path(),
+            Kind::Text(text) => skia::Path::rect(text.bounds(), skia::PathDirection::CW),
+            Kind::Bool(bool_shape) => bool_shape.to_skia_path(),
+            Kind::SVGRaw(svg_raw) => skia::Path::rect(svg_raw.bounds(), skia::PathDirection::CW),
+            // groups have no geometry of their own
+            Kind::Group => skia::Path::new(),
+        }
+    }
+
+    /// The outline grown by the given amount, or shrunk if it is negative.
+    pub fn to_outset_skia_path(&self, delta: f32) -> skia::Path {
+        match self {
+            Kind::Rect(rect, None) | Kind::Frame(rect, None) => {
+                let rect = rect.with_outset((delta, delta));
+                if rect.is_empty() {
+                    return skia::Path::new();
+                }
+                skia::Path::rect(rect, skia::PathDirection::CW)
+            }
+            // the radii grow (or shrink) along with the rect
+            Kind::Rect(rect, Some(corners)) | Kind::Frame(rect, Some(corners)) => {
+                let rrect = skia::RRect::new_rect_radii(rect, corners).with_outset((delta, delta));
+                if rrect.is_empty() {
+                    return skia::Path::new();
+                }
+                skia::Path::rrect_with_start_index(rrect, skia::PathDirection::CW, 0)
+            }
+            Kind::Circle(rect) => {
+                let rect = rect.with_outset((delta, delta));
+                if rect.is_empty() {
+                    return skia::Path::new();
+                }
+                skia::Path::oval_with_start_index(rect, skia::PathDirection::CW, 1)
+            }
+            Kind::Path(path) => outset_skia_path(&path.to_skia_path(), delta),
+            Kind::Bool(bool_shape) => outset_skia_path(&bool_shape.to_skia_path(), delta),
+            Kind::Text(text) => {
+                let rect = text.bounds().with_outset((delta, delta));
+                if rect.is_empty() {
+                    return skia::Path::new();
+                }
+                skia::Path::rect(rect, skia::PathDirection::CW)
+            }
+            Kind::SVGRaw(svg_raw) => {
+                let rect = svg_raw.bounds().with_outset((delta, delta));
+                if rect.is_empty() {
+                    return skia::Path::new();
+                }
+                skia::Path::rect(rect, skia::PathDirection::CW)
+            }
+            Kind::Group => skia::Path::new(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        match self {
+            Kind::Path(path) => path.is_open(),
+            _ => false,
+        }
+    }
+}
+
+fn outset_skia_path(path: &skia::Path, delta: f32) -> skia::Path {
+    if delta == 0. {
+        return path.to_owned();
+    }
+
+    // The outline of a stroke twice as wide as the delta covers everything within
+    // that distance of the path, so it can be added to (or removed from) the path.
+    let mut paint = skia::Paint::default();
+    paint.set_style(skia::PaintStyle::Stroke);
+    paint.set_stroke_width(2. * delta.abs());
+    paint.set_stroke_join(skia::paint::Join::Miter);
+
+    let mut stroke_outline = skia::Path::new();
+    if !skia::path_utils::fill_path_with_paint(path, &paint, &mut stroke_outline, None, None) {
+        return path.to_owned();
+    }
+
+    let op = if delta > 0. {
+        skia::PathOp::Union
+    } else {
+        skia::PathOp::Difference
+    };
+
+    path.op(&stroke_outline, op)
+        .unwrap_or_else(|| path.to_owned())
 }
 
 pub type Color = skia::Color;
 
+/// The radii of the top left, top right, bottom right and bottom left corners.
+pub type Corners = [skia::Vector; 4];
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct Shape {
@@ -31,13 +160,20 @@ pub struct Shape {
     children: Vec<Uuid>,
     kind: Kind,
     selrect: math::Rect,
+    corners: Option<Corners>,
     transform: Matrix,
     rotation: f32,
     clip_content: bool,
     fills: Vec<Fill>,
+    strokes: Vec<Stroke>,
+    shadows: Vec<Shadow>,
+    blur: Blur,
+    mask: Option<MaskType>,
     blend_mode: BlendMode,
     opacity: f32,
     hidden: bool,
+    // whether the outline may have changed since the bools were last updated
+    dirty: bool,
 }
 
 impl Shape {
@@ -45,33 +181,85 @@ impl Shape {
         Self {
             id,
             children: Vec::<Uuid>::new(),
-            kind: Kind::Rect(math::Rect::new_empty()),
+            kind: Kind::Rect(math::Rect::new_empty(), None),
             selrect: math::Rect::new_empty(),
+            corners: None,
             transform: Matrix::identity(),
             rotation: 0.,
             clip_content: true,
             fills: vec![],
+            strokes: vec![],
+            shadows: vec![],
+            blur: Blur::default(),
+            mask: None,
             blend_mode: BlendMode::default(),
             opacity: 1.,
             hidden: false,
+            dirty: true,
         }
     }
 
     pub fn set_selrect(&mut self, left: f32, top: f32, right: f32, bottom: f32) {
         self.selrect.set_ltrb(left, top, right, bottom);
+        self.dirty = true;
         match self.kind {
-            Kind::Rect(_) => {
-                self.kind = Kind::Rect(self.selrect.to_owned());
+            Kind::Rect(_, corners) => {
+                self.kind = Kind::Rect(self.selrect.to_owned(), corners);
+            }
+            Kind::Frame(_, corners) => {
+                self.kind = Kind::Frame(self.selrect.to_owned(), corners);
             }
             Kind::Circle(_) => {
                 self.kind = Kind::Circle(self.selrect.to_owned());
             }
+            Kind::Text(ref mut text) => {
+                text.set_bounds(self.selrect.to_owned());
+            }
+            Kind::SVGRaw(ref mut svg_raw) => {
+                svg_raw.set_bounds(self.selrect.to_owned());
+            }
             _ => {}
         };
     }
 
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
     pub fn set_kind(&mut self, kind: Kind) {
         self.kind = kind;
+        self.update_kind_corners();
+        self.dirty = true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn clear_dirty(&mut self) {
+        self.dirty = false;
+    }
+
+    pub fn set_corners(&mut self, radii: (f32, f32, f32, f32)) {
+        let (r1, r2, r3, r4) = radii;
+        self.corners = if r1 == 0. && r2 == 0. && r3 == 0. && r4 == 0. {
+            None
+        } else {
+            Some([r1, r2, r3, r4].map(|r| skia::Vector::new(r, r)))
+        };
+
+        self.update_kind_corners();
+        self.dirty = true;
+    }
+
+    // The corners belong to the shape, so they are kept whatever the order they are
+    // set in, and they are copied to the kinds that are drawn with them.
+    fn update_kind_corners(&mut self) {
+        match self.kind {
+            Kind::Rect(rect, _) => self.kind = Kind::Rect(rect, self.corners),
+            Kind::Frame(rect, _) => self.kind = Kind::Frame(rect, self.corners),
+            _ => {}
+        }
     }
 
     pub fn set_clip(&mut self, value: bool) {
@@ -80,10 +268,12 @@ impl Shape {
 
     pub fn set_rotation(&mut self, angle: f32) {
         self.rotation = angle;
+        self.dirty = true;
     }
 
     pub fn set_transform(&mut self, a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) {
         self.transform = Matrix::new(a, b, c, d, e, f);
+        self.dirty = true;
     }
 
     pub fn set_opacity(&mut self, opacity: f32) {
@@ -92,14 +282,21 @@ impl Shape {
 
     pub fn set_hidden(&mut self, value: bool) {
         self.hidden = value;
+        self.dirty = true;
     }
 
     pub fn add_child(&mut self, id: Uuid) {
         self.children.push(id);
+        self.dirty = true;
+    }
+
+    pub fn children(&self) -> &[Uuid] {
+        &self.children
     }
 
     pub fn clear_children(&mut self) {
         self.children.clear();
+        self.dirty = true;
     }
 
     pub fn fills(&self) -> std::slice::Iter<Fill> {
@@ -114,24 +311,214 @@ impl Shape {
         self.fills.clear();
     }
 
+    pub fn strokes(&self) -> std::slice::Iter<Stroke> {
+        self.strokes.iter()
+    }
+
+    pub fn add_stroke(&mut self, s: Stroke) {
+        self.strokes.push(s)
+    }
+
+    pub fn clear_strokes(&mut self) {
+        self.strokes.clear();
+    }
+
+    pub fn drop_shadows(&self) -> impl DoubleEndedIterator<Item = &Shadow> {
+        self.shadows
+            .iter()
+            .filter(|shadow| !shadow.hidden() && shadow.style() == ShadowStyle::Drop)
+    }
+
+    pub fn inner_shadows(&self) -> impl DoubleEndedIterator<Item = &Shadow> {
+        self.shadows
+            .iter()
+            .filter(|shadow| !shadow.hidden() && shadow.style() == ShadowStyle::Inner)
+    }
+
+    pub fn add_shadow(&mut self, shadow: Shadow) {
+        self.shadows.push(shadow);
+    }
+
+    pub fn clear_shadows(&mut self) {
+        self.shadows.clear();
+    }
+
+    pub fn set_blur(&mut self, blur: Blur) {
+        self.blur = blur;
+    }
+
+    pub fn set_mask(&mut self, mask: Option<MaskType>) {
+        self.mask = mask;
+    }
+
+    /// The matrix that places the shape in the coordinates of its parent.
+    pub fn transform_matrix(&self) -> skia::Matrix {
+        let transform = self.transform.to_skia_matrix();
+
+        // Check transform-matrix code from common/src/app/common/geom/shapes/transforms.cljc
+        let center = self.selrect.center();
+        let mut matrix = skia::Matrix::new_identity();
+        matrix.pre_translate(center);
+        matrix.pre_concat(&transform);
+        matrix.pre_translate(-center);
+
+        matrix
+    }
+
+    pub fn set_stroke_caps(
+        &mut self,
+        cap_start: StrokeCap,
+        cap_end: StrokeCap,
+    ) -> Result<(), String> {
+        let stroke = self.strokes.last_mut().ok_or("Shape has no strokes")?;
+        stroke.set_caps(cap_start, cap_end);
+        Ok(())
+    }
+
     pub fn add_gradient_stops(&mut self, buffer: Vec<RawStopData>) -> Result<(), String> {
         let fill = self.fills.last_mut().ok_or("Shape has no fills")?;
-        let gradient = match fill {
-            Fill::LinearGradient(g) => Ok(g),
-            Fill::RadialGradient(g) => Ok(g),
-            _ => Err("Active fill is not a gradient"),
-        }?;
-
-        for stop in buffer.into_iter() {
-            gradient.add_stop(stop.color(), stop.offset());
-        }
+        fill.add_gradient_stops(buffer)
+    }
 
+    pub fn set_stroke_fill(&mut self, fill: Fill) -> Result<(), String> {
+        let stroke = self.strokes.last_mut().ok_or("Shape has no strokes")?;
+        stroke.set_fill(fill);
         Ok(())
     }
 
+    pub fn add_stroke_gradient_stops(&mut self, buffer: Vec<RawStopData>) -> Result<(), String> {
+        let stroke = self.strokes.last_mut().ok_or("Shape has no strokes")?;
+        stroke.fill_mut().add_gradient_stops(buffer)
+    }
+
     pub fn set_path_segments(&mut self, buffer: Vec<RawPathData>) -> Result<(), String> {
-        let p = Path::try_from(buffer)?;
+        let mut p = Path::try_from(buffer)?;
+        // the fill rule is kept when the segments change
+        if let Kind::Path(path) = &self.kind {
+            p.set_fill_rule(path.fill_rule());
+        }
         self.kind = Kind::Path(p);
+        self.dirty = true;
+        Ok(())
+    }
+
+    pub fn set_path_fill_rule(&mut self, fill_rule: FillRule) -> Result<(), String> {
+        match &mut self.kind {
+            Kind::Path(path) => {
+                path.set_fill_rule(fill_rule);
+                self.dirty = true;
+                Ok(())
+            }
+            _ => Err("Shape is not a path".to_string()),
+        }
+    }
+
+    pub fn set_text_grow_type(&mut self, grow_type: GrowType) -> Result<(), String> {
+        match &mut self.kind {
+            Kind::Text(text) => {
+                text.set_grow_type(grow_type);
+                Ok(())
+            }
+            _ => Err("Shape is not a text".to_string()),
+        }
+    }
+
+    pub fn set_text_vertical_align(&mut self, vertical_align: VerticalAlign) -> Result<(), String> {
+        match &mut self.kind {
+            Kind::Text(text) => {
+                text.set_vertical_align(vertical_align);
+                Ok(())
+            }
+            _ => Err("Shape is not a text".to_string()),
+        }
+    }
+
+    pub fn text_dimensions(&self, fonts: &FontStore) -> Result<(f32, f32), String> {
+        match &self.kind {
+            Kind::Text(text) => Ok(text.dimensions(fonts.font_collection())),
+            _ => Err("Shape is not a text".to_string()),
+        }
+    }
+
+    /// Replaces the text with a path made of the outlines of its glyphs, and returns
+    /// the segments of that path.
+    pub fn convert_text_to_path(&mut self, fonts: &FontStore) -> Result<Vec<RawPathData>, String> {
+        let path = match &self.kind {
+            Kind::Text(text) => Path::from(&text.to_glyphs_path(fonts.font_collection())),
+            _ => return Err("Shape is not a text".to_string()),
+        };
+
+        let raw_data = path.to_raw_data();
+        self.kind = Kind::Path(path);
+        self.dirty = true;
+        Ok(raw_data)
+    }
+
+    pub fn add_text_paragraph(&mut self, paragraph: Paragraph) -> Result<(), String> {
+        match &mut self.kind {
+            Kind::Text(text) => {
+                text.add_paragraph(paragraph);
+                Ok(())
+            }
+            _ => Err("Shape is not a text".to_string()),
+        }
+    }
+
+    pub fn add_text_leaf(&mut self, leaf: TextLeaf) -> Result<(), String> {
+        match &mut self.kind {
+            Kind::Text(text) => text.add_leaf(leaf),
+            _ => Err("Shape is not a text".to_string()),
+        }
+    }
+
+    /// Updates the geometry of a bool with the outlines of its children, in the
+    /// coordinates of its parent.
+    pub fn update_bool(&mut self, sources: Vec<skia::Path>) -> Result<(), String> {
+        // the bool path is drawn with the transform of the bool itself
+        let inverse = self
+            .transform_matrix()
+            .invert()
+            .ok_or("Bool transform is not invertible")?;
+
+        match &mut self.kind {
+            Kind::Bool(bool_shape) => {
+                bool_shape.update(
+                    sources
+                        .iter()
+                        .map(|source| source.with_transform(&inverse))
+                        .collect(),
+                );
+                Ok(())
+            }
+            _ => Err("Shape is not a bool".to_string()),
+        }
+    }
+
+    pub fn add_text_leaf_fill(&mut self, fill: Fill) -> Result<(), String> {
+        match &mut self.kind {
+            Kind::Text(text) => {
+                text.last_leaf_mut()?.add_fill(fill);
+                Ok(())
+            }
+            _ => Err("Shape is not a text".to_string()),
+        }
+    }
+
+    pub fn add_text_leaf_gradient_stops(&mut self, buffer: Vec<RawStopData>) -> Result<(), String> {
+        match &mut self.kind {
+            Kind::Text(text) => text.last_leaf_mut()?.add_gradient_stops(buffer),
+            _ => Err("Shape is not a text".to_string()),
+        }
+    }
+
+    pub fn set_svg_raw_content(
+        &mut self,
+        content: String,
+        font_mgr: skia::FontMgr,
+    ) -> Result<(), String> {
+        let svg_raw = SVGRaw::from_content(content, font_mgr, self.selrect)?;
+        self.kind = Kind::SVGRaw(svg_raw);
+        self.dirty = true;
         Ok(())
     }
 
@@ -143,6 +530,7 @@ impl Shape {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::render::Renderable;
 
     fn any_shape() -> Shape {
         Shape::new(Uuid::nil())
@@ -156,4 +544,107 @@ mod tests {
         shape.add_fill(Fill::Solid(Color::TRANSPARENT));
         assert_eq!(shape.fills.get(0), Some(&Fill::Solid(Color::TRANSPARENT)))
     }
+
+    #[test]
+    fn add_stroke_pushes_a_new_stroke() {
+        let mut shape = any_shape();
+        assert_eq!(shape.strokes.len(), 0);
+
+        let stroke = Stroke::new(
+            Fill::Solid(Color::BLACK),
+            1.,
+            StrokeStyle::Solid,
+            StrokeKind::CenterStroke,
+        );
+        shape.add_stroke(stroke.clone());
+        assert_eq!(shape.strokes.get(0), Some(&stroke))
+    }
+
+    #[test]
+    fn corners_are_kept_when_the_selrect_changes() {
+        let mut shape = any_shape();
+        shape.set_corners((1., 2., 3., 4.));
+        shape.set_selrect(0., 0., 10., 10.);
+
+        let corners = [1., 2., 3., 4.].map(|r| skia::Vector::new(r, r));
+        assert_eq!(
+            shape.kind,
+            Kind::Rect(math::Rect::new(0., 0., 10., 10.), Some(corners))
+        );
+    }
+
+    #[test]
+    fn corners_are_kept_when_the_kind_changes() {
+        let mut shape = any_shape();
+        shape.set_kind(Kind::Circle(math::Rect::new_empty()));
+        shape.set_corners((1., 2., 3., 4.));
+        shape.set_kind(Kind::Frame(math::Rect::new_empty(), None));
+
+        let corners = [1., 2., 3., 4.].map(|r| skia::Vector::new(r, r));
+        assert_eq!(
+            shape.kind,
+            Kind::Frame(math::Rect::new_empty(), Some(corners))
+        );
+    }
+
+    #[test]
+    fn frames_clip_their_children_to_their_rounded_geometry() {
+        let mut shape = any_shape();
+        shape.set_kind(Kind::Frame(math::Rect::new_empty(), None));
+        shape.set_corners((5., 5., 5., 5.));
+        shape.set_selrect(0., 0., 10., 10.);
+
+        assert!(shape.clip());
+        assert_eq!(
+            shape.clip_bounds(),
+            skia::RRect::new_rect_xy(math::Rect::new(0., 0., 10., 10.), 5., 5.)
+        );
+
+        shape.set_kind(Kind::Group);
+        assert!(!shape.clip());
+    }
+
+    #[test]
+    fn path_fill_rule_is_kept_when_the_segments_change() {
+        let mut skia_path = skia::Path::new();
+        skia_path.add_rect(math::Rect::new(0., 0., 10., 10.), None);
+        skia_path.add_rect(math::Rect::new(2., 2., 8., 8.), None);
+        let path = Path::from(&skia_path);
+
+        let mut shape = any_shape();
+        shape.set_kind(Kind::Path(path.clone()));
+        shape.set_path_fill_rule(FillRule::EvenOdd).unwrap();
+        shape.set_path_segments(path.to_raw_data()).unwrap();
+
+        let outline = shape.kind.to_skia_path();
+        assert!(outline.contains((1., 1.)));
+        assert!(!outline.contains((5., 5.)));
+    }
+
+    #[test]
+    fn extrect_is_inflated_by_the_widest_outer_stroke() {
+        let mut shape = any_shape();
+        shape.set_selrect(0., 0., 10., 10.);
+        shape.add_stroke(Stroke::new(
+            Fill::Solid(Color::BLACK),
+            4.,
+            StrokeStyle::Solid,
+            StrokeKind::CenterStroke,
+        ));
+        shape.add_stroke(Stroke::new(
+            Fill::Solid(Color::BLACK),
+            3.,
+            StrokeStyle::Solid,
+            StrokeKind::OuterStroke,
+        ));
+        shape.add_stroke(Stroke::new(
+            Fill::Solid(Color::BLACK),
+            8.,
+            StrokeStyle::Solid,
+            StrokeKind::InnerStroke,
+        ));
+
+        assert_eq!(shape.extrect(), math::Rect::new(-3., -3., 13., 13.));
+        assert_eq!(shape.bounds(), math::Rect::new(0., 0., 10., 10.));
+    }
 }
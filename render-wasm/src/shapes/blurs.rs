@@ -0,0 +1,58 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlurType {
+    LayerBlur,
+    BackgroundBlur,
+}
+
+impl From<i32> for BlurType {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::BackgroundBlur,
+            _ => Self::LayerBlur,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Blur {
+    blur_type: BlurType,
+    value: f32,
+    hidden: bool,
+}
+
+impl Default for Blur {
+    fn default() -> Self {
+        Self {
+            blur_type: BlurType::LayerBlur,
+            value: 0.,
+            hidden: false,
+        }
+    }
+}
+
+impl Blur {
+    pub fn new(blur_type: BlurType, value: f32, hidden: bool) -> Self {
+        Self {
+            blur_type,
+            value,
+            hidden,
+        }
+    }
+
+    pub fn blur_type(&self) -> BlurType {
+        self.blur_type
+    }
+
+    /// The standard deviation of the gaussian blur, if it must be applied.
+    pub fn sigma(&self) -> Option<f32> {
+        if self.hidden || self.value <= 0. {
+            return None;
+        }
+
+        // Based on the blur filters from frontend/src/app/main/ui/shapes/filters.cljs
+        match self.blur_type {
+            BlurType::LayerBlur => Some(self.value),
+            BlurType::BackgroundBlur => Some(self.value / 2.),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use skia_safe as skia;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoolType {
+    Union,
+    Difference,
+    Intersection,
+    Exclude,
+}
+
+impl From<i32> for BoolType {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Difference,
+            2 => Self::Intersection,
+            3 => Self::Exclude,
+            _ => Self::Union,
+        }
+    }
+}
+
+impl BoolType {
+    fn path_op(&self) -> skia::PathOp {
+        match self {
+            Self::Union => skia::PathOp::Union,
+            Self::Difference => skia::PathOp::Difference,
+            Self::Intersection => skia::PathOp::Intersect,
+            Self::Exclude => skia::PathOp::XOR,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bool {
+    bool_type: BoolType,
+    // the outlines of the children the path was computed from
+    sources: Vec<skia::Path>,
+    path: skia::Path,
+}
+
+impl Bool {
+    pub fn new(bool_type: BoolType) -> Self {
+        Self {
+            bool_type,
+            sources: vec![],
+            path: skia::Path::new(),
+        }
+    }
+
+    pub fn to_skia_path(&self) -> skia::Path {
+        self.path.snapshot()
+    }
+
+    /// Combines the outlines of the visible children, in order, into the path of the
+    /// bool. It is only recomputed when they have changed.
+    pub fn update(&mut self, sources: Vec<skia::Path>) {
+        if sources == self.sources {
+            return;
+        }
+
+        let mut paths = sources.iter();
+        self.path = match paths.next() {
+            Some(first) => paths.fold(first.to_owned(), |path, source| {
+                path.op(source, self.bool_type.path_op()).unwrap_or(path)
+            }),
+            None => skia::Path::new(),
+        };
+        self.sources = sources;
+    }
+}
@@ -156,6 +156,20 @@ impl Fill {
         })
     }
 
+    pub fn add_gradient_stops(&mut self, buffer: Vec<RawStopData>) -> Result<(), String> {
+        let gradient = match self {
+            Fill::LinearGradient(g) => Ok(g),
+            Fill::RadialGradient(g) => Ok(g),
+            _ => Err("Active fill is not a gradient"),
+        }?;
+
+        for stop in buffer.into_iter() {
+            gradient.add_stop(stop.color(), stop.offset());
+        }
+
+        Ok(())
+    }
+
     pub fn to_paint(&self, rect: &math::Rect) -> skia::Paint {
         match self {
             Self::Solid(color) => {
@@ -1,26 +1,75 @@
 use crate::math;
-use crate::shapes::Kind;
+use crate::render::ImageStore;
+use crate::shapes::{ImageFill, Kind};
 use skia_safe as skia;
 
 pub type Image = skia::Image;
 
 pub fn draw_image_in_container(
     canvas: &skia::Canvas,
-    image: &Image,
-    size: (i32, i32),
+    images: &ImageStore,
+    image_fill: &ImageFill,
     kind: &Kind,
     paint: &skia::Paint,
 ) {
-    let width = size.0 as f32;
-    let height = size.1 as f32;
-    let image_aspect_ratio = width / height;
-
     let container = match kind {
-        Kind::Rect(r) => r.to_owned(),
+        Kind::Rect(r, _) | Kind::Frame(r, _) => r.to_owned(),
         Kind::Circle(r) => r.to_owned(),
         Kind::Path(p) => p.to_skia_path().bounds().to_owned(),
+        Kind::Text(text) => text.bounds(),
+        Kind::SVGRaw(svg_raw) => svg_raw.bounds(),
+        Kind::Bool(b) => b.to_skia_path().bounds().to_owned(),
+        // groups have no fills
+        Kind::Group => return,
     };
 
+    let dest_rect = image_cover_rect(image_fill.size(), &container);
+    let image = match images.get(&image_fill.id(), device_size(canvas, &dest_rect)) {
+        Some(image) => image,
+        // the image is not loaded yet
+        None => return,
+    };
+
+    // Save the current canvas state
+    canvas.save();
+
+    // Set the clipping rectangle to the container bounds
+    match kind {
+        Kind::Rect(_, None) | Kind::Frame(_, None) | Kind::Text(_) | Kind::SVGRaw(_) => {
+            canvas.clip_rect(container, skia::ClipOp::Intersect, true);
+        }
+        Kind::Rect(_, Some(corners)) | Kind::Frame(_, Some(corners)) => {
+            let rrect = skia::RRect::new_rect_radii(container, corners);
+            canvas.clip_rrect(rrect, skia::ClipOp::Intersect, true);
+        }
+        Kind::Circle(_) => {
+            let mut oval_path = skia::Path::new();
+            oval_path.add_oval(container, None);
+            canvas.clip_path(&oval_path, skia::ClipOp::Intersect, true);
+        }
+        Kind::Path(p) => {
+            canvas.clip_path(&p.to_skia_path(), skia::ClipOp::Intersect, true);
+        }
+        Kind::Bool(b) => {
+            canvas.clip_path(&b.to_skia_path(), skia::ClipOp::Intersect, true);
+        }
+        Kind::Group => {}
+    }
+
+    // Draw the image with the calculated destination rectangle
+    canvas.draw_image_rect(&image, None, dest_rect, &paint);
+
+    // Restore the canvas to remove the clipping
+    canvas.restore();
+}
+
+/// The rect where an image of the given size must be drawn to cover the container,
+/// centered in it.
+fn image_cover_rect(size: (i32, i32), container: &math::Rect) -> math::Rect {
+    let width = size.0 as f32;
+    let height = size.1 as f32;
+    let image_aspect_ratio = width / height;
+
     // Container size
     let container_width = container.width();
     let container_height = container.height();
@@ -43,29 +92,34 @@ pub fn draw_image_in_container(
     let offset_x = container.left + (container_width - scaled_width) / 2.0;
     let offset_y = container.top + (container_height - scaled_height) / 2.0;
 
-    let dest_rect = math::Rect::from_xywh(offset_x, offset_y, scaled_width, scaled_height);
-
-    // Save the current canvas state
-    canvas.save();
+    math::Rect::from_xywh(offset_x, offset_y, scaled_width, scaled_height)
+}
 
-    // Set the clipping rectangle to the container bounds
-    match kind {
-        Kind::Rect(_) => {
-            canvas.clip_rect(container, skia::ClipOp::Intersect, true);
-        }
-        Kind::Circle(_) => {
-            let mut oval_path = skia::Path::new();
-            oval_path.add_oval(container, None);
-            canvas.clip_path(&oval_path, skia::ClipOp::Intersect, true);
-        }
-        Kind::Path(p) => {
-            canvas.clip_path(&p.to_skia_path(), skia::ClipOp::Intersect, true);
-        }
-    }
+/// A shader that paints the image covering the container, for paints that cannot
+/// be drawn as an image (like strokes).
+pub fn image_shader_in_container(
+    canvas: &skia::Canvas,
+    images: &ImageStore,
+    image_fill: &ImageFill,
+    container: &math::Rect,
+) -> Option<skia::Shader> {
+    let dest_rect = image_cover_rect(image_fill.size(), container);
+    let image = images.get(&image_fill.id(), device_size(canvas, &dest_rect))?;
+    let src_rect = math::Rect::from_iwh(image.width(), image.height());
+    let matrix = skia::Matrix::rect_to_rect(src_rect, dest_rect, None)?;
 
-    // Draw the image with the calculated destination rectangle
-    canvas.draw_image_rect(image, None, dest_rect, &paint);
+    image.to_shader(
+        (skia::TileMode::Clamp, skia::TileMode::Clamp),
+        skia::SamplingOptions::new(skia::FilterMode::Linear, skia::MipmapMode::Nearest),
+        &matrix,
+    )
+}
 
-    // Restore the canvas to remove the clipping
-    canvas.restore();
+/// The size, in device pixels, a rect is drawn at with the current canvas transform.
+fn device_size(canvas: &skia::Canvas, rect: &math::Rect) -> skia::ISize {
+    let (device_rect, _) = canvas.local_to_device_as_3x3().map_rect(rect);
+    skia::ISize::new(
+        device_rect.width().ceil() as i32,
+        device_rect.height().ceil() as i32,
+    )
 }
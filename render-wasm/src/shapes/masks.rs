@@ -0,0 +1,14 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MaskType {
+    Alpha,
+    Luminance,
+}
+
+impl From<i32> for MaskType {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Luminance,
+            _ => Self::Alpha,
+        }
+    }
+}
@@ -18,6 +18,11 @@ impl RawPathData {
         Ok(cmd)
     }
 
+    fn flags(&self) -> Result<u16, String> {
+        let flags = u16::from_be_bytes(self.data[2..4].try_into().map_err(stringify_slice_err)?);
+        Ok(flags)
+    }
+
     fn xy(&self) -> Result<Point, String> {
         let x = f32::from_be_bytes(self.data[20..24].try_into().map_err(stringify_slice_err)?);
         let y = f32::from_be_bytes(self.data[24..].try_into().map_err(stringify_slice_err)?);
@@ -39,19 +44,148 @@ impl RawPathData {
     }
 }
 
+impl From<Segment> for RawPathData {
+    fn from(segment: Segment) -> Self {
+        let (cmd, flags, points) = match segment {
+            Segment::MoveTo(xy) => (MOVE_TO, 0, [(0., 0.), (0., 0.), xy]),
+            Segment::LineTo(xy) => (LINE_TO, 0, [(0., 0.), (0., 0.), xy]),
+            Segment::CurveTo((c1, c2, xy)) => (CURVE_TO, 0, [c1, c2, xy]),
+            Segment::QuadTo((c, xy)) => (QUAD_TO, 0, [c, (0., 0.), xy]),
+            Segment::ArcTo {
+                radii,
+                rotation,
+                large_arc,
+                sweep,
+                xy,
+            } => {
+                let mut flags = 0;
+                if large_arc {
+                    flags |= LARGE_ARC_FLAG;
+                }
+                if sweep {
+                    flags |= SWEEP_FLAG;
+                }
+                (ARC_TO, flags, [radii, (rotation, 0.), xy])
+            }
+            Segment::Close => (CLOSE, 0, [(0., 0.), (0., 0.), (0., 0.)]),
+        };
+
+        let mut data = [0; 28];
+        data[0..2].copy_from_slice(&cmd.to_be_bytes());
+        data[2..4].copy_from_slice(&flags.to_be_bytes());
+        for (i, (x, y)) in points.iter().enumerate() {
+            let offset = 4 + i * 8;
+            data[offset..offset + 4].copy_from_slice(&x.to_be_bytes());
+            data[offset + 4..offset + 8].copy_from_slice(&y.to_be_bytes());
+        }
+
+        RawPathData { data }
+    }
+}
+
 const MOVE_TO: u16 = 1;
 const LINE_TO: u16 = 2;
 const CURVE_TO: u16 = 3;
 const CLOSE: u16 = 4;
+// Like CURVE_TO, with the control point in place of the first one
+const QUAD_TO: u16 = 5;
+// Like the SVG arc command: the radii are in place of the first control point, the
+// x axis rotation (in degrees) in place of the second one's x, and the large arc and
+// sweep flags are stored in the flags
+const ARC_TO: u16 = 6;
+
+const LARGE_ARC_FLAG: u16 = 1;
+const SWEEP_FLAG: u16 = 2;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 enum Segment {
     MoveTo(Point),
     LineTo(Point),
     CurveTo((Point, Point, Point)),
+    QuadTo((Point, Point)),
+    ArcTo {
+        radii: Point,
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        xy: Point,
+    },
     Close,
 }
 
+impl Segment {
+    fn end_point(&self) -> Option<Point> {
+        match *self {
+            Segment::MoveTo(xy) => Some(xy),
+            Segment::LineTo(xy) => Some(xy),
+            Segment::CurveTo((_, _, xy)) => Some(xy),
+            Segment::QuadTo((_, xy)) => Some(xy),
+            Segment::ArcTo { xy, .. } => Some(xy),
+            Segment::Close => None,
+        }
+    }
+}
+
+/// The control points of the cubic curve that draws the same as a quadratic one.
+fn quad_to_cubic(start: Point, control: Point, end: Point) -> (Point, Point, Point) {
+    let c1 = (
+        start.0 + 2. / 3. * (control.0 - start.0),
+        start.1 + 2. / 3. * (control.1 - start.1),
+    );
+    let c2 = (
+        end.0 + 2. / 3. * (control.0 - end.0),
+        end.1 + 2. / 3. * (control.1 - end.1),
+    );
+    (c1, c2, end)
+}
+
+/// The points Skia draws an arc with. As it is made of conics, their control points
+/// lie on the tangents of the arc.
+fn arc_points(start: Point, arc: &Segment) -> Vec<Point> {
+    let mut path = skia::Path::new();
+    path.move_to(start);
+    if let Segment::ArcTo {
+        radii,
+        rotation,
+        large_arc,
+        sweep,
+        xy,
+    } = *arc
+    {
+        add_arc_to(&mut path, radii, rotation, large_arc, sweep, xy);
+    }
+    path.points().iter().map(|p| (p.x, p.y)).collect()
+}
+
+fn add_arc_to(
+    path: &mut skia::Path,
+    radii: Point,
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    xy: Point,
+) {
+    let arc_size = if large_arc {
+        skia::path::ArcSize::Large
+    } else {
+        skia::path::ArcSize::Small
+    };
+    // a positive sweep goes clockwise, as the y axis points down
+    let direction = if sweep {
+        skia::PathDirection::CW
+    } else {
+        skia::PathDirection::CCW
+    };
+    path.arc_to_rotated(radii, rotation, arc_size, direction, xy);
+}
+
+/// Direction from a point to the first of the given points that is not equal to it.
+fn direction_to(from: Point, to: &[Point]) -> Option<Point> {
+    to.iter()
+        .find(|point| **point != from)
+        .map(|(x, y)| (x - from.0, y - from.1))
+}
+
 impl TryFrom<RawPathData> for Segment {
     type Error = String;
     fn try_from(value: RawPathData) -> Result<Self, Self::Error> {
@@ -60,6 +194,17 @@ impl TryFrom<RawPathData> for Segment {
             MOVE_TO => Ok(Segment::MoveTo(value.xy()?)),
             LINE_TO => Ok(Segment::LineTo(value.xy()?)),
             CURVE_TO => Ok(Segment::CurveTo((value.c1()?, value.c2()?, value.xy()?))),
+            QUAD_TO => Ok(Segment::QuadTo((value.c1()?, value.xy()?))),
+            ARC_TO => {
+                let flags = value.flags()?;
+                Ok(Segment::ArcTo {
+                    radii: value.c1()?,
+                    rotation: value.c2()?.0,
+                    large_arc: flags & LARGE_ARC_FLAG != 0,
+                    sweep: flags & SWEEP_FLAG != 0,
+                    xy: value.xy()?,
+                })
+            }
             CLOSE => Ok(Segment::Close),
             _ => Err(format!(
                 "Error deserializing path. Unknown command/flags: {:#010x}",
@@ -69,9 +214,44 @@ impl TryFrom<RawPathData> for Segment {
     }
 }
 
+/// The rule that decides which parts of a path are inside it, like the SVG fill-rule.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillRule {
+    NonZero,
+    EvenOdd,
+}
+
+impl From<i32> for FillRule {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::EvenOdd,
+            _ => Self::NonZero,
+        }
+    }
+}
+
+impl From<skia::PathFillType> for FillRule {
+    fn from(fill_type: skia::PathFillType) -> Self {
+        match fill_type {
+            skia::PathFillType::EvenOdd | skia::PathFillType::InverseEvenOdd => Self::EvenOdd,
+            _ => Self::NonZero,
+        }
+    }
+}
+
+impl FillRule {
+    fn to_skia_fill_type(&self) -> skia::PathFillType {
+        match self {
+            Self::NonZero => skia::PathFillType::Winding,
+            Self::EvenOdd => skia::PathFillType::EvenOdd,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Path {
     segments: Vec<Segment>,
+    fill_rule: FillRule,
     skia_path: skia::Path,
 }
 
@@ -84,7 +264,56 @@ impl TryFrom<Vec<RawPathData>> for Path {
             .map(|raw| Segment::try_from(raw))
             .collect::<Result<Vec<Segment>, String>>()?;
 
+        Ok(Path::from_segments(segments, FillRule::NonZero))
+    }
+}
+
+impl From<&skia::Path> for Path {
+    /// Converts the path to the segments supported by the binary format, so quadratic
+    /// curves and conics are turned into cubic curves (as path consumers may not know
+    /// the quadratic command).
+    fn from(skia_path: &skia::Path) -> Self {
+        let mut segments = vec![];
+        let mut iter = skia::path::Iter::new(skia_path, false);
+
+        while let Some((verb, points)) = iter.next() {
+            let points: Vec<Point> = points.iter().map(|p| (p.x, p.y)).collect();
+            match verb {
+                skia::path::Verb::Move => segments.push(Segment::MoveTo(points[0])),
+                skia::path::Verb::Line => segments.push(Segment::LineTo(points[1])),
+                skia::path::Verb::Quad => {
+                    let curve = quad_to_cubic(points[0], points[1], points[2]);
+                    segments.push(Segment::CurveTo(curve));
+                }
+                skia::path::Verb::Conic => {
+                    let weight = iter.conic_weight().unwrap_or(1.);
+                    let mut quads = [skia::Point::default(); 5];
+                    let count = skia::Path::convert_conic_to_quads(
+                        points[0], points[1], points[2], weight, &mut quads, 1,
+                    )
+                    .unwrap_or(0);
+                    for i in 0..count {
+                        let [start, control, end] =
+                            [quads[2 * i], quads[2 * i + 1], quads[2 * i + 2]].map(|p| (p.x, p.y));
+                        segments.push(Segment::CurveTo(quad_to_cubic(start, control, end)));
+                    }
+                }
+                skia::path::Verb::Cubic => {
+                    segments.push(Segment::CurveTo((points[1], points[2], points[3])))
+                }
+                skia::path::Verb::Close => segments.push(Segment::Close),
+                _ => {}
+            }
+        }
+
+        Path::from_segments(segments, FillRule::from(skia_path.fill_type()))
+    }
+}
+
+impl Path {
+    fn from_segments(segments: Vec<Segment>, fill_rule: FillRule) -> Self {
         let mut skia_path = skia::Path::new();
+        skia_path.set_fill_type(fill_rule.to_skia_fill_type());
         for segment in segments.iter() {
             match *segment {
                 Segment::MoveTo(xy) => {
@@ -96,21 +325,189 @@ impl TryFrom<Vec<RawPathData>> for Path {
                 Segment::CurveTo((c1, c2, xy)) => {
                     skia_path.cubic_to(c1, c2, xy);
                 }
+                Segment::QuadTo((c, xy)) => {
+                    skia_path.quad_to(c, xy);
+                }
+                Segment::ArcTo {
+                    radii,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    xy,
+                } => {
+                    add_arc_to(&mut skia_path, radii, rotation, large_arc, sweep, xy);
+                }
                 Segment::Close => {
                     skia_path.close();
                 }
             }
         }
 
-        Ok(Path {
+        Path {
             segments,
+            fill_rule,
             skia_path,
-        })
+        }
+    }
+
+    pub fn fill_rule(&self) -> FillRule {
+        self.fill_rule
+    }
+
+    pub fn set_fill_rule(&mut self, fill_rule: FillRule) {
+        self.fill_rule = fill_rule;
+        self.skia_path.set_fill_type(fill_rule.to_skia_fill_type());
+    }
+
+    /// The segments of the path, in the binary format they are read from.
+    pub fn to_raw_data(&self) -> Vec<RawPathData> {
+        self.segments
+            .iter()
+            .map(|segment| RawPathData::from(*segment))
+            .collect()
     }
-}
 
-impl Path {
     pub fn to_skia_path(&self) -> skia::Path {
         self.skia_path.snapshot()
     }
+
+    /// The first point of the path and the direction in which the path leaves it.
+    pub fn start_tangent(&self) -> Option<(Point, Point)> {
+        let mut segments = self.segments.iter();
+        let start = match segments.next()? {
+            Segment::MoveTo(xy) => *xy,
+            _ => return None,
+        };
+
+        let direction = match *segments.next()? {
+            Segment::LineTo(xy) => direction_to(start, &[xy]),
+            Segment::CurveTo((c1, c2, xy)) => direction_to(start, &[c1, c2, xy]),
+            Segment::QuadTo((c, xy)) => direction_to(start, &[c, xy]),
+            arc @ Segment::ArcTo { .. } => direction_to(start, &arc_points(start, &arc)),
+            _ => None,
+        }?;
+
+        Some((start, direction))
+    }
+
+    /// The last point of the path and the direction in which the path arrives to it.
+    pub fn end_tangent(&self) -> Option<(Point, Point)> {
+        let (last, rest) = self.segments.split_last()?;
+        let previous = rest.iter().rev().find_map(|segment| segment.end_point())?;
+
+        match *last {
+            Segment::LineTo(xy) => {
+                let (dx, dy) = direction_to(xy, &[previous])?;
+                Some((xy, (-dx, -dy)))
+            }
+            Segment::CurveTo((c1, c2, xy)) => {
+                let (dx, dy) = direction_to(xy, &[c2, c1, previous])?;
+                Some((xy, (-dx, -dy)))
+            }
+            Segment::QuadTo((c, xy)) => {
+                let (dx, dy) = direction_to(xy, &[c, previous])?;
+                Some((xy, (-dx, -dy)))
+            }
+            arc @ Segment::ArcTo { xy, .. } => {
+                // the last point is the end of the arc
+                let points: Vec<Point> = arc_points(previous, &arc)
+                    .into_iter()
+                    .rev()
+                    .skip(1)
+                    .collect();
+                let (dx, dy) = direction_to(xy, &points)?;
+                Some((xy, (-dx, -dy)))
+            }
+            _ => None,
+        }
+    }
+
+    /// A path is open when any of its subpaths is not ended by a close command.
+    pub fn is_open(&self) -> bool {
+        let mut is_open = false;
+        for segment in self.segments.iter() {
+            match *segment {
+                Segment::MoveTo(_) if is_open => return true,
+                Segment::MoveTo(_) => {}
+                Segment::Close => is_open = false,
+                _ => is_open = true,
+            }
+        }
+        is_open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_segment(cmd: u16, flags: u16, values: [f32; 6]) -> RawPathData {
+        let mut data = [0; 28];
+        data[0..2].copy_from_slice(&cmd.to_be_bytes());
+        data[2..4].copy_from_slice(&flags.to_be_bytes());
+        for (i, value) in values.iter().enumerate() {
+            data[4 + i * 4..8 + i * 4].copy_from_slice(&value.to_be_bytes());
+        }
+        RawPathData { data }
+    }
+
+    #[test]
+    fn quad_to_is_read_as_a_quadratic_curve() {
+        let path = Path::try_from(vec![
+            raw_segment(MOVE_TO, 0, [0., 0., 0., 0., 0., 0.]),
+            raw_segment(QUAD_TO, 0, [5., 10., 0., 0., 10., 0.]),
+        ])
+        .unwrap();
+
+        let mut expected = skia::Path::new();
+        expected.move_to((0., 0.)).quad_to((5., 10.), (10., 0.));
+        assert_eq!(path.to_skia_path(), expected);
+        assert_eq!(path.start_tangent(), Some(((0., 0.), (5., 10.))));
+        assert_eq!(path.end_tangent(), Some(((10., 0.), (5., -10.))));
+    }
+
+    #[test]
+    fn arc_to_is_read_as_an_elliptical_arc() {
+        let path = Path::try_from(vec![
+            raw_segment(MOVE_TO, 0, [0., 0., 0., 0., 0., 0.]),
+            raw_segment(ARC_TO, SWEEP_FLAG, [5., 5., 0., 0., 10., 0.]),
+        ])
+        .unwrap();
+
+        let mut expected = skia::Path::new();
+        expected.move_to((0., 0.)).arc_to_rotated(
+            (5., 5.),
+            0.,
+            skia::path::ArcSize::Small,
+            skia::PathDirection::CW,
+            (10., 0.),
+        );
+        assert_eq!(path.to_skia_path(), expected);
+
+        // the half circle goes over the center, so it starts going up and ends going down
+        let (start, (dx, dy)) = path.start_tangent().unwrap();
+        assert_eq!(start, (0., 0.));
+        assert!(dx.abs() < 1e-4 && dy < 0.);
+        let (end, (dx, dy)) = path.end_tangent().unwrap();
+        assert_eq!(end, (10., 0.));
+        assert!(dx.abs() < 1e-4 && dy > 0.);
+    }
+
+    #[test]
+    fn quad_and_arc_segments_are_written_back_unchanged() {
+        let raw_segments = vec![
+            raw_segment(MOVE_TO, 0, [0., 0., 0., 0., 0., 0.]),
+            raw_segment(QUAD_TO, 0, [5., 10., 0., 0., 10., 0.]),
+            raw_segment(
+                ARC_TO,
+                LARGE_ARC_FLAG | SWEEP_FLAG,
+                [4., 8., 30., 0., 20., 5.],
+            ),
+        ];
+        let expected: Vec<[u8; 28]> = raw_segments.iter().map(|raw| raw.data).collect();
+
+        let path = Path::try_from(raw_segments).unwrap();
+        let data: Vec<[u8; 28]> = path.to_raw_data().iter().map(|raw| raw.data).collect();
+        assert_eq!(data, expected);
+    }
 }
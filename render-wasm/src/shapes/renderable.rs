@@ -1,27 +1,51 @@
 use skia_safe as skia;
 use uuid::Uuid;
 
-use super::{draw_image_in_container, Fill, Kind, Shape};
-use crate::math::Rect;
-use crate::render::{ImageStore, Renderable};
+use super::{
+    draw_image_in_container, image_shader_in_container, outset_skia_path, BlurType, Fill, Kind,
+    MaskType, Path, Shadow, Shape, Stroke, StrokeKind,
+};
+use crate::math::{Point, Rect};
+use crate::render::{FontStore, ImageStore, Renderable};
 
 impl Renderable for Shape {
-    fn render(&self, surface: &mut skia_safe::Surface, images: &ImageStore) -> Result<(), String> {
-        let transform = self.transform.to_skia_matrix();
+    fn render(
+        &self,
+        surface: &mut skia_safe::Surface,
+        images: &ImageStore,
+        fonts: &FontStore,
+    ) -> Result<(), String> {
+        surface.canvas().concat(&self.transform_matrix());
 
-        // Check transform-matrix code from common/src/app/common/geom/shapes/transforms.cljc
-        let center = self.selrect.center();
-        let mut matrix = skia::Matrix::new_identity();
-        matrix.pre_translate(center);
-        matrix.pre_concat(&transform);
-        matrix.pre_translate(-center);
+        // Text leaves carry their own styles and fills, so texts only paint their paragraphs
+        if let Kind::Text(text) = &self.kind {
+            text.draw(surface.canvas(), fonts.font_collection(), images);
+            return Ok(());
+        }
 
-        surface.canvas().concat(&matrix);
+        // Groups draw nothing, they only composite their children
+        if let Kind::Group = self.kind {
+            return Ok(());
+        }
 
-        for fill in self.fills().rev() {
-            render_fill(surface, images, fill, self.selrect, &self.kind);
+        // Imported SVGs carry their own styles too
+        if let Kind::SVGRaw(svg_raw) = &self.kind {
+            svg_raw.draw(surface.canvas());
+            return Ok(());
+        }
+
+        for shadow in self.drop_shadows().rev() {
+            self.render_drop_shadow(surface, shadow);
+        }
+
+        self.render_fills(surface, images);
+
+        for shadow in self.inner_shadows().rev() {
+            render_inner_shadow(surface, shadow, &self.kind);
         }
 
+        self.render_strokes(surface, images);
+
         let mut paint = skia::Paint::default();
         paint.set_blend_mode(self.blend_mode.into());
         paint.set_alpha_f(self.opacity);
@@ -45,15 +69,150 @@ impl Renderable for Shape {
         self.selrect
     }
 
+    fn clip_bounds(&self) -> skia::RRect {
+        match &self.kind {
+            Kind::Rect(_, Some(corners)) | Kind::Frame(_, Some(corners)) => {
+                skia::RRect::new_rect_radii(self.bounds(), corners)
+            }
+            _ => skia::RRect::new_rect(self.bounds()),
+        }
+    }
+
+    fn extrect(&self) -> Rect {
+        let stroke_width = self
+            .strokes()
+            .map(|stroke| {
+                // open paths are always stroked on the center
+                if self.kind.is_open() {
+                    stroke.outer_width().max(stroke.width() / 2.)
+                } else {
+                    stroke.outer_width()
+                }
+            })
+            .fold(0., f32::max);
+
+        let mut rect = self.selrect.with_outset((stroke_width, stroke_width));
+
+        let stroked_rect = self
+            .selrect
+            .with_outset((self.strokes_outset(), self.strokes_outset()));
+        for shadow in self.drop_shadows() {
+            rect.join(shadow.bounds(&stroked_rect));
+        }
+
+        if let Some(sigma) = self.layer_blur() {
+            // the gaussian blur is negligible beyond three times its sigma
+            rect.outset((3. * sigma, 3. * sigma));
+        }
+
+        rect
+    }
+
+    fn outline(&self) -> skia::Path {
+        self.kind
+            .to_skia_path()
+            .with_transform(&self.transform_matrix())
+    }
+
+    fn layer_blur(&self) -> Option<f32> {
+        match self.blur.blur_type() {
+            BlurType::LayerBlur => self.blur.sigma(),
+            _ => None,
+        }
+    }
+
+    fn background_blur(&self) -> Option<f32> {
+        match self.blur.blur_type() {
+            BlurType::BackgroundBlur => self.blur.sigma(),
+            _ => None,
+        }
+    }
+
+    fn mask(&self) -> Option<MaskType> {
+        self.mask
+    }
+
+    fn is_composited(&self) -> bool {
+        matches!(self.kind, Kind::Group | Kind::Frame(..))
+    }
+
+    // Frames clip their content, and so do rects, as boards may still be set as rects
     fn clip(&self) -> bool {
-        self.clip_content
+        matches!(self.kind, Kind::Frame(..) | Kind::Rect(..)) && self.clip_content
     }
 
     fn children_ids(&self) -> Vec<Uuid> {
-        self.children.clone()
+        match self.kind {
+            // the children of a bool only give shape to it, they are not drawn
+            Kind::Bool(_) => vec![],
+            _ => self.children.clone(),
+        }
     }
 }
 
+impl Shape {
+    // Like in the SVG renderer, every fill is drawn below every stroke, and
+    // both lists are drawn in reverse order (the first item is the top-most).
+    fn render_fills(&self, surface: &mut skia::Surface, images: &ImageStore) {
+        for fill in self.fills().rev() {
+            render_fill(surface, images, fill, self.selrect, &self.kind);
+        }
+    }
+
+    fn render_strokes(&self, surface: &mut skia::Surface, images: &ImageStore) {
+        for stroke in self.strokes().rev() {
+            render_stroke(surface, images, stroke, self.selrect, &self.kind);
+        }
+    }
+
+    // The shadow is cast by the shape outline when it is filled and by the outlines of
+    // its strokes (ignoring the cap markers), all of them dilated by the spread. So
+    // shapes without fills (like open paths) only cast the shadow of their strokes.
+    fn render_drop_shadow(&self, surface: &mut skia::Surface, shadow: &Shadow) {
+        let mut path = if !self.fills.is_empty() {
+            self.kind.to_outset_skia_path(shadow.spread())
+        } else {
+            skia::Path::new()
+        };
+
+        for stroke in self.strokes().filter(|stroke| stroke.width() > 0.) {
+            let outline = outset_skia_path(&stroke_outline(stroke, &self.kind), shadow.spread());
+            path = path.op(&outline, skia::PathOp::Union).unwrap_or(path);
+        }
+
+        surface.canvas().draw_path(&path, &shadow.to_paint());
+    }
+
+    /// How far the strokes reach beyond the shape outline, ignoring the cap markers.
+    fn strokes_outset(&self) -> f32 {
+        self.strokes()
+            .filter(|stroke| stroke.width() > 0.)
+            .map(|stroke| stroke.outset())
+            .fold(0., f32::max)
+    }
+}
+
+fn render_inner_shadow(surface: &mut skia::Surface, shadow: &Shadow, kind: &Kind) {
+    let outline = kind.to_skia_path();
+
+    // The shadow is cast by everything outside the shape eroded by the spread, and
+    // it is only painted inside the shape outline.
+    let eroded = kind.to_outset_skia_path(-shadow.spread());
+    let area = outline
+        .bounds()
+        .with_outset((shadow.reach() + 1., shadow.reach() + 1.));
+    let casting = match skia::Path::rect(area, None).op(&eroded, skia::PathOp::Difference) {
+        Some(path) => path,
+        None => return,
+    };
+
+    let canvas = surface.canvas();
+    canvas.save();
+    canvas.clip_path(&outline, skia::ClipOp::Intersect, true);
+    canvas.draw_path(&casting, &shadow.to_paint());
+    canvas.restore();
+}
+
 fn render_fill(
     surface: &mut skia::Surface,
     images: &ImageStore,
@@ -63,20 +222,21 @@ fn render_fill(
 ) {
     match (fill, kind) {
         (Fill::Image(image_fill), kind) => {
-            let image = images.get(&image_fill.id());
-            if let Some(image) = image {
-                draw_image_in_container(
-                    surface.canvas(),
-                    &image,
-                    image_fill.size(),
-                    kind,
-                    &fill.to_paint(&selrect),
-                );
-            }
+            draw_image_in_container(
+                surface.canvas(),
+                images,
+                image_fill,
+                kind,
+                &fill.to_paint(&selrect),
+            );
         }
-        (_, Kind::Rect(rect)) => {
+        (_, Kind::Rect(rect, None) | Kind::Frame(rect, None)) => {
             surface.canvas().draw_rect(rect, &fill.to_paint(&selrect));
         }
+        (_, Kind::Rect(rect, Some(corners)) | Kind::Frame(rect, Some(corners))) => {
+            let rrect = skia::RRect::new_rect_radii(rect, corners);
+            surface.canvas().draw_rrect(rrect, &fill.to_paint(&selrect));
+        }
         (_, Kind::Circle(rect)) => {
             surface.canvas().draw_oval(rect, &fill.to_paint(&selrect));
         }
@@ -85,5 +245,152 @@ fn render_fill(
                 .canvas()
                 .draw_path(&path.to_skia_path(), &fill.to_paint(&selrect));
         }
+        (_, Kind::Bool(bool_shape)) => {
+            surface
+                .canvas()
+                .draw_path(&bool_shape.to_skia_path(), &fill.to_paint(&selrect));
+        }
+        // imported SVGs are painted with their own fills
+        (_, Kind::SVGRaw(_)) => {}
+        // text fills are painted by their leaves
+        (_, Kind::Text(_)) => {}
+        (_, Kind::Group) => {}
+    }
+}
+
+fn render_stroke(
+    surface: &mut skia::Surface,
+    images: &ImageStore,
+    stroke: &Stroke,
+    selrect: Rect,
+    kind: &Kind,
+) {
+    if stroke.width() <= 0. {
+        return;
     }
+
+    let outer_rect = stroke.outer_rect(&selrect);
+    let mut paint = stroke.to_paint(&outer_rect);
+
+    if let Fill::Image(image_fill) = stroke.fill() {
+        let shader = image_shader_in_container(surface.canvas(), images, image_fill, &outer_rect);
+
+        match shader {
+            Some(shader) => paint.set_shader(shader),
+            // the image is not loaded yet
+            None => return,
+        };
+    }
+
+    let canvas = surface.canvas();
+    let path = kind.to_skia_path();
+
+    // Skia only strokes on the center of the outline, so (like the SVG renderer does)
+    // inner and outer strokes are drawn with double width and clipped by the shape.
+    match stroke_kind(stroke, kind) {
+        StrokeKind::CenterStroke => {
+            if let Some(cap) = stroke.line_cap() {
+                paint.set_stroke_cap(cap);
+            }
+            canvas.draw_path(&path, &paint);
+        }
+        StrokeKind::InnerStroke => {
+            paint.set_stroke_width(2. * stroke.width());
+            canvas.save();
+            canvas.clip_path(&path, skia::ClipOp::Intersect, true);
+            canvas.draw_path(&path, &paint);
+            canvas.restore();
+        }
+        StrokeKind::OuterStroke => {
+            paint.set_stroke_width(2. * stroke.width());
+            canvas.save();
+            canvas.clip_path(&path, skia::ClipOp::Difference, true);
+            canvas.draw_path(&path, &paint);
+            canvas.restore();
+        }
+    }
+
+    if let Kind::Path(path) = kind {
+        if path.is_open() {
+            render_stroke_cap_markers(canvas, stroke, path, &paint);
+        }
+    }
+}
+
+// Open paths have no inside or outside, so they are always stroked on the center.
+fn stroke_kind(stroke: &Stroke, kind: &Kind) -> StrokeKind {
+    if kind.is_open() {
+        StrokeKind::CenterStroke
+    } else {
+        stroke.kind()
+    }
+}
+
+/// The area painted by the stroke, ignoring its cap markers.
+fn stroke_outline(stroke: &Stroke, kind: &Kind) -> skia::Path {
+    let path = kind.to_skia_path();
+    let mut paint = stroke.to_paint(&path.bounds());
+
+    let stroke_kind = stroke_kind(stroke, kind);
+    match stroke_kind {
+        StrokeKind::CenterStroke => {
+            if let Some(cap) = stroke.line_cap() {
+                paint.set_stroke_cap(cap);
+            }
+        }
+        StrokeKind::InnerStroke | StrokeKind::OuterStroke => {
+            paint.set_stroke_width(2. * stroke.width());
+        }
+    }
+
+    let mut outline = skia::Path::new();
+    if !skia::path_utils::fill_path_with_paint(&path, &paint, &mut outline, None, None) {
+        return skia::Path::new();
+    }
+
+    // inner and outer strokes are clipped by the shape, like when they are drawn
+    let clipped = match stroke_kind {
+        StrokeKind::CenterStroke => Some(outline),
+        StrokeKind::InnerStroke => outline.op(&path, skia::PathOp::Intersect),
+        StrokeKind::OuterStroke => outline.op(&path, skia::PathOp::Difference),
+    };
+    clipped.unwrap_or_else(skia::Path::new)
+}
+
+fn render_stroke_cap_markers(
+    canvas: &skia::Canvas,
+    stroke: &Stroke,
+    path: &Path,
+    stroke_paint: &skia::Paint,
+) {
+    let mut paint = stroke_paint.clone();
+    paint.set_style(skia::PaintStyle::Fill);
+    paint.set_path_effect(None);
+
+    let (start_marker, end_marker) = stroke.cap_markers();
+
+    if let (Some(marker), Some((xy, (dx, dy)))) = (start_marker, path.start_tangent()) {
+        // The start marker points backwards, like orient="auto-start-reverse" in SVG
+        render_marker(canvas, &marker, xy, (-dx, -dy), stroke.width(), &paint);
+    }
+
+    if let (Some(marker), Some((xy, direction))) = (end_marker, path.end_tangent()) {
+        render_marker(canvas, &marker, xy, direction, stroke.width(), &paint);
+    }
+}
+
+fn render_marker(
+    canvas: &skia::Canvas,
+    marker: &skia::Path,
+    xy: Point,
+    direction: Point,
+    width: f32,
+    paint: &skia::Paint,
+) {
+    let mut matrix = skia::Matrix::new_identity();
+    matrix.pre_translate(xy);
+    matrix.pre_rotate(direction.1.atan2(direction.0).to_degrees(), None);
+    matrix.pre_scale((width, width), None);
+
+    canvas.draw_path(&marker.with_transform(&matrix), paint);
 }
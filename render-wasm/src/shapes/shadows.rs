@@ -0,0 +1,97 @@
+use skia_safe as skia;
+
+use super::Color;
+use crate::math::Rect;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShadowStyle {
+    Drop,
+    Inner,
+}
+
+impl From<i32> for ShadowStyle {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Inner,
+            _ => Self::Drop,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shadow {
+    color: Color,
+    blur: f32,
+    spread: f32,
+    offset: (f32, f32),
+    style: ShadowStyle,
+    hidden: bool,
+}
+
+impl Shadow {
+    pub fn new(
+        color: Color,
+        blur: f32,
+        spread: f32,
+        offset: (f32, f32),
+        style: ShadowStyle,
+        hidden: bool,
+    ) -> Self {
+        Self {
+            color,
+            blur,
+            spread,
+            offset,
+            style,
+            hidden,
+        }
+    }
+
+    pub fn hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn style(&self) -> ShadowStyle {
+        self.style
+    }
+
+    pub fn spread(&self) -> f32 {
+        self.spread
+    }
+
+    /// The area painted by the shadow of a shape with the given bounds.
+    pub fn bounds(&self, rect: &Rect) -> Rect {
+        // the gaussian blur is negligible beyond three times its sigma
+        let outset = self.spread + 3. * self.sigma();
+        rect.with_outset((outset, outset)).with_offset(self.offset)
+    }
+
+    /// How far the shadow of a point can be painted from it.
+    pub fn reach(&self) -> f32 {
+        self.offset.0.abs().max(self.offset.1.abs()) + 3. * self.sigma()
+    }
+
+    // Based on the shadow filters from frontend/src/app/main/ui/shapes/filters.cljs
+    fn sigma(&self) -> f32 {
+        self.blur / 2.
+    }
+
+    fn image_filter(&self) -> Option<skia::ImageFilter> {
+        let filter = skia::image_filters::offset(self.offset, None, None);
+        if self.blur > 0. {
+            skia::image_filters::blur((self.sigma(), self.sigma()), None, filter, None)
+        } else {
+            filter
+        }
+    }
+
+    pub fn to_paint(&self) -> skia::Paint {
+        let mut p = skia::Paint::default();
+        p.set_color(self.color);
+        p.set_style(skia::PaintStyle::Fill);
+        p.set_anti_alias(true);
+        p.set_blend_mode(skia::BlendMode::SrcOver);
+        p.set_image_filter(self.image_filter());
+        p
+    }
+}
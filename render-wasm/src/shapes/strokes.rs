@@ -0,0 +1,271 @@
+use skia_safe as skia;
+
+use super::Fill;
+use crate::math::{Point, Rect};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StrokeStyle {
+    Solid,
+    Dotted,
+    Dashed,
+    Mixed,
+}
+
+impl From<i32> for StrokeStyle {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Dotted,
+            2 => Self::Dashed,
+            3 => Self::Mixed,
+            _ => Self::Solid,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StrokeKind {
+    CenterStroke,
+    InnerStroke,
+    OuterStroke,
+}
+
+impl From<i32> for StrokeKind {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::InnerStroke,
+            2 => Self::OuterStroke,
+            _ => Self::CenterStroke,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StrokeCap {
+    None,
+    LineArrow,
+    TriangleArrow,
+    SquareMarker,
+    CircleMarker,
+    DiamondMarker,
+    Round,
+    Square,
+}
+
+impl From<i32> for StrokeCap {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::LineArrow,
+            2 => Self::TriangleArrow,
+            3 => Self::SquareMarker,
+            4 => Self::CircleMarker,
+            5 => Self::DiamondMarker,
+            6 => Self::Round,
+            7 => Self::Square,
+            _ => Self::None,
+        }
+    }
+}
+
+impl StrokeCap {
+    fn is_line_cap(&self) -> bool {
+        matches!(self, Self::Round | Self::Square)
+    }
+
+    // Based on the markers from frontend/src/app/main/ui/shapes/custom_stroke.cljs.
+    // The geometry is scaled from the marker viewBox to stroke width units, with its
+    // reference point at the origin and pointing to the positive x axis.
+    fn marker(&self) -> Option<skia::Path> {
+        match self {
+            Self::None => None,
+            Self::LineArrow => Some(marker_polygon(
+                &[
+                    (0.5, 0.5),
+                    (3., 3.),
+                    (0.5, 5.5),
+                    (0., 5.),
+                    (2., 3.),
+                    (0., 1.),
+                ],
+                (2., 3.),
+                8.5 / 6.,
+            )),
+            Self::TriangleArrow => Some(marker_polygon(
+                &[(0., 0.), (3., 3.), (0., 6.)],
+                (2., 3.),
+                8.5 / 6.,
+            )),
+            Self::SquareMarker => {
+                let size = 4.2426;
+                let rect = Rect::from_xywh(-size / 2., -size / 2., size, size);
+                Some(skia::Path::rect(rect, None))
+            }
+            Self::CircleMarker => Some(skia::Path::circle((0., 0.), 2., None)),
+            Self::DiamondMarker => Some(marker_polygon(
+                &[(3., 0.), (6., 3.), (3., 6.), (0., 3.)],
+                (3., 3.),
+                1.,
+            )),
+            Self::Round => {
+                let mut path = skia::Path::new();
+                path.move_to((0., -0.5));
+                path.arc_to_rotated(
+                    (0.5, 0.5),
+                    0.,
+                    skia::path::ArcSize::Small,
+                    skia::PathDirection::CW,
+                    (0., 0.5),
+                );
+                path.close();
+                Some(path)
+            }
+            Self::Square => Some(skia::Path::rect(Rect::from_xywh(0., -0.5, 0.5, 1.), None)),
+        }
+    }
+}
+
+fn marker_polygon(points: &[Point], reference: Point, scale: f32) -> skia::Path {
+    let points: Vec<skia::Point> = points
+        .iter()
+        .map(|(x, y)| skia::Point::new((x - reference.0) * scale, (y - reference.1) * scale))
+        .collect();
+    skia::Path::polygon(&points, true, None, None)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stroke {
+    fill: Fill,
+    width: f32,
+    style: StrokeStyle,
+    kind: StrokeKind,
+    cap_start: StrokeCap,
+    cap_end: StrokeCap,
+}
+
+impl Stroke {
+    pub fn new(fill: Fill, width: f32, style: StrokeStyle, kind: StrokeKind) -> Self {
+        Self {
+            fill,
+            width,
+            style,
+            kind,
+            cap_start: StrokeCap::None,
+            cap_end: StrokeCap::None,
+        }
+    }
+
+    pub fn set_caps(&mut self, cap_start: StrokeCap, cap_end: StrokeCap) {
+        self.cap_start = cap_start;
+        self.cap_end = cap_end;
+    }
+
+    pub fn fill(&self) -> &Fill {
+        &self.fill
+    }
+
+    pub fn fill_mut(&mut self) -> &mut Fill {
+        &mut self.fill
+    }
+
+    pub fn set_fill(&mut self, fill: Fill) {
+        self.fill = fill;
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn kind(&self) -> StrokeKind {
+        self.kind
+    }
+
+    /// How far the stroke reaches beyond the shape outline.
+    pub fn outset(&self) -> f32 {
+        match self.kind {
+            StrokeKind::CenterStroke => self.width / 2.,
+            StrokeKind::InnerStroke => 0.,
+            StrokeKind::OuterStroke => self.width,
+        }
+    }
+
+    /// How far the stroke (including its cap markers) reaches beyond the shape outline.
+    pub fn outer_width(&self) -> f32 {
+        let stroke_width = self.outset();
+
+        let (start_marker, end_marker) = self.cap_markers();
+        [start_marker, end_marker]
+            .iter()
+            .flatten()
+            .map(|marker| {
+                let bounds = marker.bounds();
+                let size = bounds
+                    .left
+                    .abs()
+                    .max(bounds.right.abs())
+                    .max(bounds.top.abs())
+                    .max(bounds.bottom.abs());
+                size * self.width
+            })
+            .fold(stroke_width, f32::max)
+    }
+
+    /// The bounds of the stroked shape, used to place the stroke gradients and images.
+    pub fn outer_rect(&self, rect: &Rect) -> Rect {
+        rect.with_outset((self.outset(), self.outset()))
+    }
+
+    pub fn to_paint(&self, rect: &Rect) -> skia::Paint {
+        let mut p = self.fill.to_paint(rect);
+        p.set_style(skia::PaintStyle::Stroke);
+        p.set_stroke_width(self.width);
+        p.set_anti_alias(true);
+
+        if self.style == StrokeStyle::Dotted {
+            p.set_stroke_cap(skia::paint::Cap::Round);
+        }
+
+        if let Some(path_effect) = self.dash_path_effect() {
+            p.set_path_effect(path_effect);
+        }
+
+        p
+    }
+
+    /// The paint cap, used when both ends of the stroke share the same line cap.
+    pub fn line_cap(&self) -> Option<skia::paint::Cap> {
+        if self.style == StrokeStyle::Dotted || self.cap_start != self.cap_end {
+            return None;
+        }
+
+        match self.cap_start {
+            StrokeCap::Round => Some(skia::paint::Cap::Round),
+            StrokeCap::Square => Some(skia::paint::Cap::Square),
+            _ => None,
+        }
+    }
+
+    /// The markers to draw at the start and the end of open paths, in stroke width
+    /// units. Line caps are drawn as markers when each end has a different one.
+    pub fn cap_markers(&self) -> (Option<skia::Path>, Option<skia::Path>) {
+        let is_marker = |cap: StrokeCap| !cap.is_line_cap() || self.cap_start != self.cap_end;
+
+        let start = Some(self.cap_start)
+            .filter(|cap| is_marker(*cap))
+            .and_then(|cap| cap.marker());
+        let end = Some(self.cap_end)
+            .filter(|cap| is_marker(*cap))
+            .and_then(|cap| cap.marker());
+
+        (start, end)
+    }
+
+    // Based on the dash arrays from frontend/src/app/main/ui/shapes/attrs.cljs
+    fn dash_path_effect(&self) -> Option<skia::PathEffect> {
+        let w = self.width;
+        match self.style {
+            StrokeStyle::Solid => None,
+            StrokeStyle::Dotted => skia::PathEffect::dash(&[0., w + 5.], 0.),
+            StrokeStyle::Dashed => skia::PathEffect::dash(&[w + 10., w + 10.], 0.),
+            StrokeStyle::Mixed => skia::PathEffect::dash(&[w + 5., w + 5., w + 1., w + 5.], 0.),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use skia_safe::{self as skia, svg};
+
+use crate::math::Rect;
+
+/// An imported SVG, rendered with the SVG DOM of Skia.
+#[derive(Debug, Clone)]
+pub struct SVGRaw {
+    content: String,
+    dom: svg::Dom,
+    bounds: Rect,
+}
+
+// The DOM is parsed from the content, so SVGs are compared by their content
+impl PartialEq for SVGRaw {
+    fn eq(&self, other: &Self) -> bool {
+        self.content == other.content && self.bounds == other.bounds
+    }
+}
+
+impl SVGRaw {
+    pub fn from_content(
+        content: String,
+        font_mgr: skia::FontMgr,
+        bounds: Rect,
+    ) -> Result<Self, String> {
+        let dom = svg::Dom::from_str(&content, font_mgr).map_err(|err| err.to_string())?;
+
+        let mut svg_raw = Self {
+            content,
+            dom,
+            bounds,
+        };
+        svg_raw.set_bounds(bounds);
+        Ok(svg_raw)
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Sets the box where the SVG is drawn. Its size is used to resolve the viewport
+    /// of SVGs without width and height, like the size of an SVG element in a page.
+    pub fn set_bounds(&mut self, bounds: Rect) {
+        self.bounds = bounds;
+        self.dom.set_container_size(bounds.size());
+    }
+
+    pub fn draw(&self, canvas: &skia::Canvas) {
+        canvas.save();
+        canvas.translate((self.bounds.left, self.bounds.top));
+        // SVGs with their own width and height are scaled to fill the box
+        let size = self.dom.root().intrinsic_size();
+        if !size.is_empty() {
+            canvas.scale((
+                self.bounds.width() / size.width,
+                self.bounds.height() / size.height,
+            ));
+        }
+        self.dom.render(canvas);
+        canvas.restore();
+    }
+}
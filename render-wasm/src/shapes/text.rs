@@ -0,0 +1,746 @@
+use skia_safe::{self as skia, textlayout};
+use std::array::TryFromSliceError;
+use uuid::Uuid;
+
+use super::{image_shader_in_container, Color, Fill, RawStopData};
+use crate::math::Rect;
+use crate::render::{ImageStore, DEFAULT_FONT};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+}
+
+impl From<i32> for FontStyle {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Italic,
+            _ => Self::Normal,
+        }
+    }
+}
+
+impl FontStyle {
+    fn slant(&self) -> skia::font_style::Slant {
+        match self {
+            Self::Normal => skia::font_style::Slant::Upright,
+            Self::Italic => skia::font_style::Slant::Italic,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GrowType {
+    Fixed,
+    AutoWidth,
+    AutoHeight,
+}
+
+impl From<i32> for GrowType {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::AutoWidth,
+            2 => Self::AutoHeight,
+            _ => Self::Fixed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextDecoration {
+    None,
+    Underline,
+    LineThrough,
+}
+
+impl From<u8> for TextDecoration {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::Underline,
+            2 => Self::LineThrough,
+            _ => Self::None,
+        }
+    }
+}
+
+impl TextDecoration {
+    fn to_skia_decoration(&self) -> textlayout::TextDecoration {
+        match self {
+            Self::None => textlayout::TextDecoration::NO_DECORATION,
+            Self::Underline => textlayout::TextDecoration::UNDERLINE,
+            Self::LineThrough => textlayout::TextDecoration::LINE_THROUGH,
+        }
+    }
+
+    /// The distance from the baseline to the middle of the decoration line, and its
+    /// thickness, taken from the font metrics with the same defaults skia uses.
+    fn placement(&self, metrics: &skia::FontMetrics, font_size: f32) -> Option<(f32, f32)> {
+        let underline_thickness = metrics
+            .underline_thickness()
+            .filter(|thickness| *thickness > 0.)
+            .unwrap_or(font_size / 14.);
+
+        match self {
+            Self::None => None,
+            Self::Underline => {
+                let position = metrics
+                    .underline_position()
+                    .filter(|position| *position > 0.)
+                    .unwrap_or(underline_thickness);
+                Some((position, underline_thickness))
+            }
+            Self::LineThrough => {
+                let thickness = metrics
+                    .strikeout_thickness()
+                    .filter(|thickness| *thickness > 0.)
+                    .unwrap_or(underline_thickness);
+                let position = metrics
+                    .strikeout_position()
+                    .unwrap_or(-metrics.x_height / 2.);
+                Some((position, thickness))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextTransform {
+    None,
+    Uppercase,
+    Lowercase,
+    Capitalize,
+}
+
+impl From<u8> for TextTransform {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::Uppercase,
+            2 => Self::Lowercase,
+            3 => Self::Capitalize,
+            _ => Self::None,
+        }
+    }
+}
+
+impl TextTransform {
+    fn apply(&self, text: &str) -> String {
+        match self {
+            Self::None => text.to_owned(),
+            Self::Uppercase => text.to_uppercase(),
+            Self::Lowercase => text.to_lowercase(),
+            // like CSS, only the first letter of each word changes
+            Self::Capitalize => {
+                let mut is_word_start = true;
+                text.chars()
+                    .map(|c| {
+                        let capitalized = if is_word_start {
+                            c.to_uppercase().collect()
+                        } else {
+                            c.to_string()
+                        };
+                        is_word_start = c.is_whitespace();
+                        capitalized
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+fn stringify_slice_err(_: TryFromSliceError) -> String {
+    format!("Error deserializing text leaf")
+}
+
+pub const RAW_TEXT_LEAF_HEADER_SIZE: usize = 36;
+
+/// The styles of a text leaf, serialized as big-endian values:
+///
+/// | bytes  | value                                                       |
+/// |--------|-------------------------------------------------------------|
+/// | 0..16  | font family id                                              |
+/// | 16..20 | font size (f32)                                             |
+/// | 20..24 | font weight (i32)                                           |
+/// | 24     | font style (0 normal, 1 italic)                             |
+/// | 25     | text decoration (0 none, 1 underline, 2 line-through)       |
+/// | 26     | text transform (0 none, 1 upper, 2 lower, 3 capitalize)     |
+/// | 27     | unused                                                      |
+/// | 28..32 | letter spacing in pixels (f32)                              |
+/// | 32..36 | line height, relative to the font size (f32)                |
+///
+/// The header is followed by the UTF-8 encoded text of the leaf. Its fills are
+/// added afterwards, like the ones of shapes.
+#[derive(Debug)]
+pub struct RawTextLeafData {
+    pub data: [u8; RAW_TEXT_LEAF_HEADER_SIZE],
+}
+
+impl RawTextLeafData {
+    fn font_family(&self) -> Result<Uuid, String> {
+        let bytes = self.data[0..16].try_into().map_err(stringify_slice_err)?;
+        Ok(Uuid::from_bytes(bytes))
+    }
+
+    fn font_size(&self) -> Result<f32, String> {
+        let bytes = self.data[16..20].try_into().map_err(stringify_slice_err)?;
+        Ok(f32::from_be_bytes(bytes))
+    }
+
+    fn font_weight(&self) -> Result<i32, String> {
+        let bytes = self.data[20..24].try_into().map_err(stringify_slice_err)?;
+        Ok(i32::from_be_bytes(bytes))
+    }
+
+    fn font_style(&self) -> FontStyle {
+        FontStyle::from(self.data[24] as i32)
+    }
+
+    fn text_decoration(&self) -> TextDecoration {
+        TextDecoration::from(self.data[25])
+    }
+
+    fn text_transform(&self) -> TextTransform {
+        TextTransform::from(self.data[26])
+    }
+
+    fn letter_spacing(&self) -> Result<f32, String> {
+        let bytes = self.data[28..32].try_into().map_err(stringify_slice_err)?;
+        Ok(f32::from_be_bytes(bytes))
+    }
+
+    fn line_height(&self) -> Result<f32, String> {
+        let bytes = self.data[32..36].try_into().map_err(stringify_slice_err)?;
+        Ok(f32::from_be_bytes(bytes))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VerticalAlign {
+    Top,
+    Center,
+    Bottom,
+}
+
+impl From<i32> for VerticalAlign {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Center,
+            2 => Self::Bottom,
+            _ => Self::Top,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+    Justify,
+}
+
+impl From<i32> for TextAlign {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Center,
+            2 => Self::Right,
+            3 => Self::Justify,
+            _ => Self::Left,
+        }
+    }
+}
+
+impl TextAlign {
+    fn to_skia_align(&self) -> textlayout::TextAlign {
+        match self {
+            Self::Left => textlayout::TextAlign::Left,
+            Self::Center => textlayout::TextAlign::Center,
+            Self::Right => textlayout::TextAlign::Right,
+            Self::Justify => textlayout::TextAlign::Justify,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLeaf {
+    text: String,
+    font_family: Uuid,
+    font_size: f32,
+    font_weight: i32,
+    font_style: FontStyle,
+    text_decoration: TextDecoration,
+    text_transform: TextTransform,
+    fills: Vec<Fill>,
+    letter_spacing: f32,
+    line_height: f32,
+}
+
+impl TryFrom<Vec<u8>> for TextLeaf {
+    type Error = String;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        if bytes.len() < RAW_TEXT_LEAF_HEADER_SIZE {
+            return Err("Error deserializing text leaf".to_string());
+        }
+        let (header, text) = bytes.split_at(RAW_TEXT_LEAF_HEADER_SIZE);
+        let raw = RawTextLeafData {
+            data: header.try_into().map_err(stringify_slice_err)?,
+        };
+        let text = String::from_utf8(text.to_vec()).map_err(|_| "Invalid UTF-8 text")?;
+
+        Ok(Self {
+            text,
+            font_family: raw.font_family()?,
+            font_size: raw.font_size()?,
+            font_weight: raw.font_weight()?,
+            font_style: raw.font_style(),
+            text_decoration: raw.text_decoration(),
+            text_transform: raw.text_transform(),
+            fills: vec![],
+            letter_spacing: raw.letter_spacing()?,
+            line_height: raw.line_height()?,
+        })
+    }
+}
+
+impl TextLeaf {
+    pub fn add_fill(&mut self, fill: Fill) {
+        self.fills.push(fill);
+    }
+
+    pub fn add_gradient_stops(&mut self, buffer: Vec<RawStopData>) -> Result<(), String> {
+        let fill = self.fills.last_mut().ok_or("Text leaf has no fills")?;
+        fill.add_gradient_stops(buffer)
+    }
+
+    /// The paint for the fill at the given index, with gradients and images placed
+    /// in the text box. None if the leaf has no such fill or its image is not loaded.
+    fn fill_paint(
+        &self,
+        index: usize,
+        rect: &Rect,
+        canvas: &skia::Canvas,
+        images: &ImageStore,
+    ) -> Option<skia::Paint> {
+        let fill = self.fills.get(index)?;
+        let mut paint = fill.to_paint(rect);
+
+        if let Fill::Image(image_fill) = fill {
+            paint.set_shader(image_shader_in_container(canvas, images, image_fill, rect)?);
+        }
+
+        Some(paint)
+    }
+
+    /// The style of the leaf. Its glyphs are painted with the given paint, or they are
+    /// left unpainted when there is none.
+    fn to_style(&self, paint: Option<&skia::Paint>) -> textlayout::TextStyle {
+        let mut style = textlayout::TextStyle::new();
+        match paint {
+            Some(paint) => {
+                style.set_foreground_paint(paint);
+                style.set_decoration_type(self.text_decoration.to_skia_decoration());
+                // skia paints decorations with a solid color, so the ones of gradients
+                // and images are left transparent and painted apart (see draw_decorations)
+                let decoration_color = match paint.shader() {
+                    Some(_) => Color::TRANSPARENT,
+                    None => paint.color(),
+                };
+                style.set_decoration_color(decoration_color);
+            }
+            None => {
+                style.set_color(Color::TRANSPARENT);
+            }
+        }
+        style.set_font_size(self.font_size);
+        // glyphs missing from the font are taken from the default one
+        style.set_font_families(&[self.font_family.to_string().as_str(), DEFAULT_FONT]);
+        style.set_font_style(skia::FontStyle::new(
+            skia::font_style::Weight::from(self.font_weight),
+            skia::font_style::Width::NORMAL,
+            self.font_style.slant(),
+        ));
+        style.set_letter_spacing(self.letter_spacing);
+
+        // Like CSS line-height, the leading is split evenly above and below the line
+        if self.line_height > 0. {
+            style.set_height(self.line_height);
+            style.set_height_override(true);
+            style.set_half_leading(true);
+        }
+
+        style
+    }
+
+    fn text(&self) -> String {
+        self.text_transform.apply(&self.text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paragraph {
+    children: Vec<TextLeaf>,
+    text_align: TextAlign,
+    paragraph_spacing: f32,
+}
+
+impl Paragraph {
+    pub fn new(text_align: TextAlign, paragraph_spacing: f32) -> Self {
+        Self {
+            children: vec![],
+            text_align,
+            paragraph_spacing,
+        }
+    }
+
+    fn to_paragraph(
+        &self,
+        fonts: &textlayout::FontCollection,
+        leaf_paint: &impl Fn(&TextLeaf) -> Option<skia::Paint>,
+    ) -> textlayout::Paragraph {
+        let mut paragraph_style = textlayout::ParagraphStyle::new();
+        paragraph_style.set_text_align(self.text_align.to_skia_align());
+        // so empty paragraphs take the line height of their first leaf
+        if let Some(leaf) = self.children.first() {
+            paragraph_style.set_text_style(&leaf.to_style(None));
+        }
+
+        let mut builder = textlayout::ParagraphBuilder::new(&paragraph_style, fonts.clone());
+        for leaf in self.children.iter() {
+            builder.push_style(&leaf.to_style(leaf_paint(leaf).as_ref()));
+            builder.add_text(leaf.text());
+            builder.pop();
+        }
+        builder.build()
+    }
+
+    /// Paints the decorations of the leaves painted with a gradient or an image over
+    /// the laid out paragraph, where skia would have painted them.
+    fn draw_decorations(
+        &self,
+        canvas: &skia::Canvas,
+        paragraph: &textlayout::Paragraph,
+        origin: skia::Point,
+        leaf_paint: &impl Fn(&TextLeaf) -> Option<skia::Paint>,
+    ) {
+        let lines = paragraph.get_line_metrics();
+
+        // the fonts are found by UTF-8 index, but the text boxes by UTF-16 index
+        let mut utf8_start = 0;
+        let mut utf16_start = 0;
+        for leaf in self.children.iter() {
+            let text = leaf.text();
+            let font_index = utf8_start;
+            let range = utf16_start..utf16_start + text.encode_utf16().count();
+            utf8_start += text.len();
+            utf16_start = range.end;
+
+            if leaf.text_decoration == TextDecoration::None {
+                continue;
+            }
+            let paint = match leaf_paint(leaf) {
+                Some(paint) if paint.shader().is_some() => paint,
+                _ => continue,
+            };
+            let font = paragraph.get_font_at(font_index);
+            let (_, metrics) = font.metrics();
+            let placement = leaf.text_decoration.placement(&metrics, font.size());
+            let (position, thickness) = match placement {
+                Some(placement) => placement,
+                None => continue,
+            };
+
+            let text_boxes = paragraph.get_rects_for_range(
+                range,
+                textlayout::RectHeightStyle::Tight,
+                textlayout::RectWidthStyle::Tight,
+            );
+            for text_box in text_boxes {
+                // the box is on the first line that reaches below its center
+                let center = f64::from(text_box.rect.center_y());
+                let line = lines
+                    .iter()
+                    .find(|line| center <= line.baseline + line.descent);
+
+                if let Some(line) = line {
+                    let y = line.baseline as f32 + position;
+                    let rect = Rect::new(
+                        text_box.rect.left,
+                        y - thickness / 2.,
+                        text_box.rect.right,
+                        y + thickness / 2.,
+                    );
+                    canvas.draw_rect(rect.with_offset(origin), &paint);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextContent {
+    paragraphs: Vec<Paragraph>,
+    bounds: Rect,
+    grow_type: GrowType,
+    vertical_align: VerticalAlign,
+}
+
+impl TextContent {
+    pub fn new(bounds: Rect) -> Self {
+        Self {
+            paragraphs: vec![],
+            bounds,
+            grow_type: GrowType::Fixed,
+            vertical_align: VerticalAlign::Top,
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    pub fn set_bounds(&mut self, bounds: Rect) {
+        self.bounds = bounds;
+    }
+
+    pub fn set_grow_type(&mut self, grow_type: GrowType) {
+        self.grow_type = grow_type;
+    }
+
+    pub fn set_vertical_align(&mut self, vertical_align: VerticalAlign) {
+        self.vertical_align = vertical_align;
+    }
+
+    pub fn add_paragraph(&mut self, paragraph: Paragraph) {
+        self.paragraphs.push(paragraph);
+    }
+
+    pub fn add_leaf(&mut self, leaf: TextLeaf) -> Result<(), String> {
+        let paragraph = self.paragraphs.last_mut().ok_or("Text has no paragraphs")?;
+        paragraph.children.push(leaf);
+        Ok(())
+    }
+
+    pub fn last_leaf_mut(&mut self) -> Result<&mut TextLeaf, String> {
+        self.paragraphs
+            .last_mut()
+            .and_then(|paragraph| paragraph.children.last_mut())
+            .ok_or_else(|| "Text has no leaves".to_string())
+    }
+
+    /// Builds and lays out the paragraphs, along with the distance from the top of
+    /// the text to each one. Auto-width texts are not wrapped, so they are as wide
+    /// as their longest line; other texts wrap to the text box width.
+    pub fn to_paragraphs(
+        &self,
+        fonts: &textlayout::FontCollection,
+    ) -> Vec<(f32, textlayout::Paragraph)> {
+        self.to_painted_paragraphs(fonts, |_| None)
+    }
+
+    fn to_painted_paragraphs(
+        &self,
+        fonts: &textlayout::FontCollection,
+        leaf_paint: impl Fn(&TextLeaf) -> Option<skia::Paint>,
+    ) -> Vec<(f32, textlayout::Paragraph)> {
+        let width = match self.grow_type {
+            GrowType::AutoWidth => f32::MAX,
+            GrowType::Fixed | GrowType::AutoHeight => self.bounds.width(),
+        };
+
+        let mut paragraphs: Vec<textlayout::Paragraph> = self
+            .paragraphs
+            .iter()
+            .map(|paragraph| {
+                let mut paragraph = paragraph.to_paragraph(fonts, &leaf_paint);
+                paragraph.layout(width);
+                paragraph
+            })
+            .collect();
+
+        if self.grow_type == GrowType::AutoWidth {
+            let width = content_width(paragraphs.iter());
+            for paragraph in paragraphs.iter_mut() {
+                paragraph.layout(width);
+            }
+        }
+
+        // the paragraph spacing separates a paragraph from the next one
+        let mut y = 0.;
+        self.paragraphs
+            .iter()
+            .zip(paragraphs)
+            .map(|(paragraph, skia_paragraph)| {
+                let top = y;
+                y += skia_paragraph.height() + paragraph.paragraph_spacing;
+                (top, skia_paragraph)
+            })
+            .collect()
+    }
+
+    /// The size of the laid out text. Fixed texts keep the width of their box, but
+    /// their content can still be taller (or shorter) than it.
+    pub fn dimensions(&self, fonts: &textlayout::FontCollection) -> (f32, f32) {
+        let paragraphs = self.to_paragraphs(fonts);
+
+        let width = match self.grow_type {
+            GrowType::AutoWidth => content_width(paragraphs.iter().map(|(_, p)| p)),
+            GrowType::Fixed | GrowType::AutoHeight => self.bounds.width(),
+        };
+        (width, content_height(&paragraphs))
+    }
+
+    /// Where the first paragraph starts, so the text is aligned vertically in its box.
+    fn content_top(&self, paragraphs: &[(f32, textlayout::Paragraph)]) -> f32 {
+        let free_space = self.bounds.height() - content_height(paragraphs);
+        match self.vertical_align {
+            VerticalAlign::Top => self.bounds.top,
+            VerticalAlign::Center => self.bounds.top + free_space / 2.,
+            VerticalAlign::Bottom => self.bounds.top + free_space,
+        }
+    }
+
+    /// The outlines of the glyphs, placed like they are painted. Glyphs without an
+    /// outline (like emojis) are left out.
+    pub fn to_glyphs_path(&self, fonts: &textlayout::FontCollection) -> skia::Path {
+        let paragraphs = self.to_paragraphs(fonts);
+        let content_top = self.content_top(&paragraphs);
+
+        let mut path = skia::Path::new();
+        for (top, mut paragraph) in paragraphs {
+            for line in 0..paragraph.line_number() {
+                let (_, line_path) = paragraph.get_path_at(line);
+                path.add_path(&line_path, (self.bounds.left, content_top + top), None);
+            }
+        }
+        path
+    }
+
+    /// Paints the paragraphs one below the other, aligned vertically in the text box.
+    /// The text is painted once for each fill of its leaves and, like the fills of the
+    /// shapes, the first fill of each leaf is painted on top.
+    pub fn draw(
+        &self,
+        canvas: &skia::Canvas,
+        fonts: &textlayout::FontCollection,
+        images: &ImageStore,
+    ) {
+        let fill_count = self
+            .paragraphs
+            .iter()
+            .flat_map(|paragraph| paragraph.children.iter())
+            .map(|leaf| leaf.fills.len())
+            .max()
+            .unwrap_or(0);
+
+        for index in (0..fill_count).rev() {
+            let leaf_paint = |leaf: &TextLeaf| leaf.fill_paint(index, &self.bounds, canvas, images);
+            let paragraphs = self.to_painted_paragraphs(fonts, &leaf_paint);
+            let content_top = self.content_top(&paragraphs);
+            for (paragraph, (top, skia_paragraph)) in self.paragraphs.iter().zip(paragraphs) {
+                let origin = skia::Point::new(self.bounds.left, content_top + top);
+                skia_paragraph.paint(canvas, origin);
+                paragraph.draw_decorations(canvas, &skia_paragraph, origin, &leaf_paint);
+            }
+        }
+    }
+}
+
+fn content_height(paragraphs: &[(f32, textlayout::Paragraph)]) -> f32 {
+    paragraphs
+        .last()
+        .map(|(top, paragraph)| top + paragraph.height())
+        .unwrap_or(0.)
+}
+
+fn content_width<'a>(paragraphs: impl Iterator<Item = &'a textlayout::Paragraph>) -> f32 {
+    paragraphs
+        .map(|paragraph| paragraph.max_intrinsic_width().ceil())
+        .fold(0., f32::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf_bytes(style: u8, decoration: u8, transform: u8, text: &str) -> Vec<u8> {
+        let family = Uuid::from_u128(0x1234);
+        let mut bytes = Vec::with_capacity(RAW_TEXT_LEAF_HEADER_SIZE + text.len());
+        bytes.extend_from_slice(family.as_bytes());
+        bytes.extend_from_slice(&14.5f32.to_be_bytes());
+        bytes.extend_from_slice(&700i32.to_be_bytes());
+        bytes.extend_from_slice(&[style, decoration, transform, 0]);
+        bytes.extend_from_slice(&0.5f32.to_be_bytes());
+        bytes.extend_from_slice(&1.2f32.to_be_bytes());
+        bytes.extend_from_slice(text.as_bytes());
+        bytes
+    }
+
+    #[test]
+    fn text_leaf_is_read_from_its_header_and_text() {
+        let leaf = TextLeaf::try_from(leaf_bytes(1, 2, 3, "héllo")).unwrap();
+
+        assert_eq!(
+            leaf,
+            TextLeaf {
+                text: "héllo".to_string(),
+                font_family: Uuid::from_u128(0x1234),
+                font_size: 14.5,
+                font_weight: 700,
+                font_style: FontStyle::Italic,
+                text_decoration: TextDecoration::LineThrough,
+                text_transform: TextTransform::Capitalize,
+                fills: vec![],
+                letter_spacing: 0.5,
+                line_height: 1.2,
+            }
+        );
+    }
+
+    #[test]
+    fn text_leaf_with_a_truncated_header_is_an_error() {
+        let mut bytes = leaf_bytes(0, 0, 0, "");
+        bytes.pop();
+
+        assert!(TextLeaf::try_from(bytes).is_err());
+    }
+
+    #[test]
+    fn text_leaf_with_invalid_utf8_text_is_an_error() {
+        let mut bytes = leaf_bytes(0, 0, 0, "");
+        bytes.extend_from_slice(&[0xff, 0xfe]);
+
+        assert!(TextLeaf::try_from(bytes).is_err());
+    }
+
+    #[test]
+    fn unknown_text_leaf_enum_values_are_read_as_the_defaults() {
+        let leaf = TextLeaf::try_from(leaf_bytes(9, 9, 9, "text")).unwrap();
+
+        assert_eq!(leaf.font_style, FontStyle::Normal);
+        assert_eq!(leaf.text_decoration, TextDecoration::None);
+        assert_eq!(leaf.text_transform, TextTransform::None);
+    }
+
+    #[test]
+    fn text_leaf_decorations_take_the_color_of_solid_paints() {
+        let leaf = TextLeaf::try_from(leaf_bytes(0, 1, 0, "text")).unwrap();
+        let mut paint = skia::Paint::default();
+        paint.set_color(Color::RED);
+
+        let style = leaf.to_style(Some(&paint));
+
+        assert_eq!(style.decoration_color(), Color::RED);
+    }
+
+    #[test]
+    fn text_leaf_decorations_are_left_transparent_for_shader_paints() {
+        let leaf = TextLeaf::try_from(leaf_bytes(0, 1, 0, "text")).unwrap();
+        let mut paint = skia::Paint::default();
+        paint.set_shader(skia::shaders::color(Color::RED));
+
+        let style = leaf.to_style(Some(&paint));
+
+        assert_eq!(style.decoration_color(), Color::TRANSPARENT);
+    }
+}
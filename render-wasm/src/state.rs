@@ -1,8 +1,9 @@
+use skia_safe as skia;
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::render::RenderState;
-use crate::shapes::Shape;
+use crate::render::{RenderState, Renderable};
+use crate::shapes::{Kind, RawPathData, Shape};
 
 /// This struct holds the state of the Rust application between JS calls.
 ///
@@ -34,14 +35,133 @@ impl<'a> State<'a> {
         &mut self.render_state
     }
 
-    pub fn navigate(&mut self) {
-        // TODO: propagate error to main fn
-        let _ = self.render_state.navigate(&self.shapes).unwrap();
+    /// Renders the shapes after a pan or zoom. Bools that could not be updated keep
+    /// their previous path.
+    pub fn navigate(&mut self) -> Result<(), String> {
+        let result = self.update_bools();
+        self.render_state.navigate(&self.shapes)?;
+        result
     }
 
-    pub fn render_all(&mut self, generate_cached_surface_image: bool) {
+    /// Renders all the shapes. Bools that could not be updated keep their previous path.
+    pub fn render_all(&mut self, generate_cached_surface_image: bool) -> Result<(), String> {
+        let result = self.update_bools();
         self.render_state
             .render_all(&self.shapes, generate_cached_surface_image);
+        result
+    }
+
+    /// Renders a frame with its content, for exports and thumbnails, encoded as PNG.
+    pub fn render_frame_image(&mut self, id: &Uuid, scale: f32) -> Result<Vec<u8>, String> {
+        let shape = self.shapes.get(id).ok_or("Shape not found")?;
+        if !matches!(shape.kind(), Kind::Frame(..)) {
+            return Err("Shape is not a frame".to_string());
+        }
+
+        // a bool that could not be updated keeps its previous path, which is better
+        // than not exporting the frame at all
+        if let Err(msg) = self.update_bools() {
+            eprintln!("{}", msg);
+        }
+        self.render_state
+            .render_shape_image(id, &self.shapes, scale)
+    }
+
+    /// Updates the paths of the bools with changes in their children.
+    fn update_bools(&mut self) -> Result<(), String> {
+        let ids: Vec<Uuid> = self
+            .shapes
+            .iter()
+            .filter(|(_, shape)| matches!(shape.kind(), Kind::Bool(_)))
+            .map(|(id, _)| *id)
+            .collect();
+
+        let mut updated = HashMap::new();
+        let mut result = Ok(());
+        for id in ids.iter() {
+            if let Err(msg) = self.update_bool(id, &mut updated) {
+                result = Err(msg);
+            }
+        }
+
+        for shape in self.shapes.values_mut() {
+            shape.clear_dirty();
+        }
+        result
+    }
+
+    // Nested bools are updated first, as their paths are part of the parent ones.
+    // Returns whether the path of the bool has changed.
+    fn update_bool(
+        &mut self,
+        id: &Uuid,
+        updated: &mut HashMap<Uuid, bool>,
+    ) -> Result<bool, String> {
+        if let Some(changed) = updated.get(id) {
+            return Ok(*changed);
+        }
+
+        let (children, mut changed) = match self.shapes.get(id) {
+            Some(shape) => (shape.children().to_vec(), shape.is_dirty()),
+            None => return Ok(false),
+        };
+        for child_id in children.iter() {
+            changed = self.update_sources(child_id, updated)? || changed;
+        }
+
+        if changed {
+            let sources = children
+                .iter()
+                .filter_map(|child_id| self.source_outline(child_id))
+                .collect();
+            if let Some(shape) = self.shapes.get_mut(id) {
+                shape.update_bool(sources)?;
+            }
+        }
+
+        updated.insert(*id, changed);
+        Ok(changed)
+    }
+
+    // Updates the bools in the given subtree, and returns whether any outline in it
+    // has changed.
+    fn update_sources(
+        &mut self,
+        id: &Uuid,
+        updated: &mut HashMap<Uuid, bool>,
+    ) -> Result<bool, String> {
+        let (children, dirty) = match self.shapes.get(id) {
+            Some(shape) => match shape.kind() {
+                Kind::Bool(_) => return self.update_bool(id, updated),
+                Kind::Group => (shape.children().to_vec(), shape.is_dirty()),
+                _ => return Ok(shape.is_dirty()),
+            },
+            None => return Ok(false),
+        };
+
+        let mut changed = dirty;
+        for child_id in children.iter() {
+            changed = self.update_sources(child_id, updated)? || changed;
+        }
+        Ok(changed)
+    }
+
+    // The outline a shape contributes to a bool. Groups have no outline of their own,
+    // so it is the union of the outlines of their children.
+    fn source_outline(&self, id: &Uuid) -> Option<skia::Path> {
+        let shape = self.shapes.get(id)?;
+        if shape.hidden() {
+            return None;
+        }
+
+        match shape.kind() {
+            Kind::Group => shape
+                .children()
+                .iter()
+                .filter_map(|child_id| self.source_outline(child_id))
+                .reduce(|path, outline| path.op(&outline, skia::PathOp::Union).unwrap_or(path)),
+            _ => Some(shape.outline()),
+        }
     }
 
     pub fn use_shape(&'a mut self, id: Uuid) {
@@ -54,6 +174,22 @@ impl<'a> State<'a> {
         self.current_shape = self.shapes.get_mut(&id);
     }
 
+    pub fn current_text_dimensions(&self) -> Result<(f32, f32), String> {
+        let shape = self
+            .current_id
+            .and_then(|id| self.shapes.get(&id))
+            .ok_or("No shape in use")?;
+        shape.text_dimensions(self.render_state.fonts())
+    }
+
+    pub fn convert_current_text_to_path(&mut self) -> Result<Vec<RawPathData>, String> {
+        let shape = self
+            .current_id
+            .and_then(|id| self.shapes.get_mut(&id))
+            .ok_or("No shape in use")?;
+        shape.convert_text_to_path(self.render_state.fonts())
+    }
+
     pub fn current_shape(&'a mut self) -> Option<&'a mut Shape> {
         self.current_shape.as_deref_mut()
     }